@@ -1,6 +1,6 @@
-/// ASCII art logos for various Linux distributions and operating systems
-/// Based on the pfetch project by Dylan Araps
-/// https://github.com/dylanaraps/pfetch
+//! ASCII art logos for various Linux distributions and operating systems
+//! Based on the pfetch project by Dylan Araps
+//! https://github.com/dylanaraps/pfetch
 
 pub struct Logo {
     pub lines: Vec<&'static str>,
@@ -390,6 +390,38 @@ pub fn get_distro_logo(distro_name: &str) -> Option<Logo> {
     }
 }
 
+/// Logo for a container, so it does not pass for the distribution
+/// installed on bare metal.
+pub fn get_container_logo(container: &str) -> Logo {
+    match container {
+        "Docker" => Logo {
+            lines: vec![
+                "         ##        .",
+                "   ## ## ##       ==",
+                "## ## ## ##      ===",
+                "/\"\"\"\"\"\"\"\"\"\"\"\"\"\"\"\"\"\\___/ ===",
+                "{                  /  ===-",
+                "\\______ o       __/",
+                "  \\    \\     __/",
+                "   \\____\\___/",
+            ],
+            colors: vec![C4], // Blue
+        },
+        _ => Logo {
+            lines: vec![
+                "   .--------.",
+                "  /        /|",
+                " +--------+ |",
+                " | [====] | |",
+                " | [====] | +",
+                " | [====] |/",
+                " +--------+",
+            ],
+            colors: vec![C3], // Yellow
+        },
+    }
+}
+
 pub fn get_default_logo() -> Logo {
     Logo {
         lines: vec![
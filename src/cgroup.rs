@@ -0,0 +1,209 @@
+//! Resource limits of the cgroup zfetch runs in.
+//!
+//! Inside a container /proc/meminfo and sysfs still describe the host, so
+//! the memory and CPU modules compare them against these limits. Limits set
+//! on a parent cgroup apply too, so the whole path up to the root is read.
+
+use std::path::{Path, PathBuf};
+use crate::Source;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+// cgroup v1 reports "no limit" as the largest page aligned i64
+const V1_UNLIMITED: u64 = 0x7fff_ffff_ffff_f000;
+
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// memory.max of the most restrictive cgroup, in bytes
+    pub memory_max: Option<u64>,
+    /// Memory charged to that cgroup, page cache included
+    pub memory_current: Option<u64>,
+    /// CPU time quota in CPUs, e.g. 1.5 for "150000 100000"
+    pub cpu_quota: Option<f64>,
+    /// CPUs the cgroup may run on
+    pub cpuset: Option<Vec<usize>>,
+}
+
+impl Limits {
+    pub fn read(source: &Source) -> Self {
+        let cgroups = source.read_to_string("/proc/self/cgroup").unwrap_or_default();
+        if source.exists(format!("{}/cgroup.controllers", CGROUP_ROOT)) {
+            read_v2(source, &cgroups)
+        } else {
+            read_v1(source, &cgroups)
+        }
+    }
+
+    /// CPUs available after both the quota and the cpuset.
+    pub fn cpus(&self) -> Option<f64> {
+        let cpuset = self.cpuset.as_ref().map(|cpus| cpus.len() as f64);
+        match (self.cpu_quota, cpuset) {
+            (Some(quota), Some(cpuset)) => Some(quota.min(cpuset)),
+            (quota, cpuset) => quota.or(cpuset),
+        }
+    }
+}
+
+/// The unified hierarchy: "0::/user.slice/..." in /proc/self/cgroup.
+fn read_v2(source: &Source, cgroups: &str) -> Limits {
+    let path = cgroups.lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .unwrap_or("/");
+    let mut limits = Limits::default();
+
+    for dir in ancestors(source, Path::new(CGROUP_ROOT), path) {
+        let read = |file: &str| source.read_trimmed(dir.join(file));
+
+        if let Some(max) = read("memory.max").and_then(|max| max.parse::<u64>().ok()) {
+            if limits.memory_max.is_none_or(|limit| max < limit) {
+                limits.memory_max = Some(max);
+                limits.memory_current = read("memory.current").and_then(|current| current.parse().ok());
+            }
+        }
+
+        // "max 100000" when unlimited, otherwise "<quota> <period>"
+        if let Some(quota) = read("cpu.max").and_then(|max| parse_quota(&max)) {
+            limits.cpu_quota = Some(limits.cpu_quota.map_or(quota, |limit| limit.min(quota)));
+        }
+
+        // The effective set already accounts for the parents
+        if limits.cpuset.is_none() {
+            limits.cpuset = read("cpuset.cpus.effective")
+                .filter(|cpus| !cpus.is_empty())
+                .map(|cpus| parse_cpu_list(&cpus));
+        }
+    }
+
+    limits
+}
+
+/// One hierarchy per controller: "4:memory:/docker/<id>".
+fn read_v1(source: &Source, cgroups: &str) -> Limits {
+    let controller_path = |controller: &str| {
+        cgroups.lines().find_map(|line| {
+            let mut fields = line.splitn(3, ':');
+            let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+            controllers.split(',').any(|name| name == controller).then_some(path)
+        })
+    };
+    let mut limits = Limits::default();
+
+    if let Some(path) = controller_path("memory") {
+        for dir in ancestors(source, &Path::new(CGROUP_ROOT).join("memory"), path) {
+            let read = |file: &str| source.read_trimmed(dir.join(file))?.parse::<u64>().ok();
+            let Some(max) = read("memory.limit_in_bytes").filter(|&max| max < V1_UNLIMITED) else {
+                continue;
+            };
+            if limits.memory_max.is_none_or(|limit| max < limit) {
+                limits.memory_max = Some(max);
+                limits.memory_current = read("memory.usage_in_bytes");
+            }
+        }
+    }
+
+    if let Some(path) = controller_path("cpu") {
+        for dir in ancestors(source, &Path::new(CGROUP_ROOT).join("cpu"), path) {
+            let read = |file: &str| source.read_trimmed(dir.join(file))?.parse::<i64>().ok();
+            // A quota of -1 means unlimited
+            let (Some(quota), Some(period)) = (read("cpu.cfs_quota_us"), read("cpu.cfs_period_us")) else {
+                continue;
+            };
+            if quota > 0 && period > 0 {
+                let quota = quota as f64 / period as f64;
+                limits.cpu_quota = Some(limits.cpu_quota.map_or(quota, |limit| limit.min(quota)));
+            }
+        }
+    }
+
+    if let Some(path) = controller_path("cpuset") {
+        let dir = ancestors(source, &Path::new(CGROUP_ROOT).join("cpuset"), path).into_iter().next();
+        limits.cpuset = dir.and_then(|dir| {
+            source.read_trimmed(dir.join("cpuset.effective_cpus"))
+                .or_else(|| source.read_trimmed(dir.join("cpuset.cpus")))
+        })
+            .filter(|cpus| !cpus.is_empty())
+            .map(|cpus| parse_cpu_list(&cpus));
+    }
+
+    limits
+}
+
+/// Directories of the cgroup at `path` and its parents, innermost first.
+/// In a cgroup namespace /proc/self/cgroup can name a path that is not
+/// mounted; the mount root is then the container's own cgroup.
+fn ancestors(source: &Source, mount: &Path, path: &str) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Path::new(path)
+        .ancestors()
+        .map(|ancestor| mount.join(ancestor.strip_prefix("/").unwrap_or(ancestor)))
+        .filter(|dir| source.exists(dir))
+        .collect();
+    if dirs.is_empty() {
+        dirs.push(mount.to_path_buf());
+    }
+    dirs
+}
+
+fn parse_quota(max: &str) -> Option<f64> {
+    let (quota, period) = max.split_once(' ')?;
+    let (quota, period) = (quota.parse::<f64>().ok()?, period.parse::<f64>().ok()?);
+    (period > 0.0).then(|| quota / period)
+}
+
+/// "0-3,8-11" -> [0, 1, 2, 3, 8, 9, 10, 11].
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.split(',')
+        .filter_map(|range| {
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_the_tightest_v2_limits() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/self/cgroup", "0::/system.slice/docker-0123.scope\n")
+            .file("/sys/fs/cgroup/cgroup.controllers", "cpuset cpu memory\n")
+            .file("/sys/fs/cgroup/system.slice/memory.max", "8589934592\n")
+            .file("/sys/fs/cgroup/system.slice/cpu.max", "max 100000\n")
+            .file("/sys/fs/cgroup/system.slice/docker-0123.scope/memory.max", "2147483648\n")
+            .file("/sys/fs/cgroup/system.slice/docker-0123.scope/memory.current", "536870912\n")
+            .file("/sys/fs/cgroup/system.slice/docker-0123.scope/cpu.max", "150000 100000\n")
+            .file("/sys/fs/cgroup/system.slice/docker-0123.scope/cpuset.cpus.effective", "0-3\n");
+
+        let limits = Limits::read(&fixture.source());
+        assert_eq!(limits.memory_max, Some(2 << 30));
+        assert_eq!(limits.memory_current, Some(512 << 20));
+        assert_eq!(limits.cpu_quota, Some(1.5));
+        assert_eq!(limits.cpuset, Some(vec![0, 1, 2, 3]));
+        assert_eq!(limits.cpus(), Some(1.5));
+    }
+
+    #[test]
+    fn unlimited_v1_cgroups_set_nothing() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/self/cgroup", "5:memory:/\n4:cpu,cpuacct:/\n")
+            .file("/sys/fs/cgroup/memory/memory.limit_in_bytes", "9223372036854771712\n")
+            .file("/sys/fs/cgroup/cpu/cpu.cfs_quota_us", "-1\n")
+            .file("/sys/fs/cgroup/cpu/cpu.cfs_period_us", "100000\n");
+
+        let limits = Limits::read(&fixture.source());
+        assert_eq!(limits.memory_max, None);
+        assert_eq!(limits.cpu_quota, None);
+        assert_eq!(limits.cpus(), None);
+    }
+
+    #[test]
+    fn parses_cpu_lists() {
+        assert_eq!(parse_cpu_list("0-3,8-11"), vec![0, 1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(parse_cpu_list("5"), vec![5]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+    }
+}
@@ -1,4 +1,7 @@
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::Registry;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -8,6 +11,22 @@ pub struct Config {
     pub json_output: bool,
     pub help: bool,
     pub version: bool,
+    pub root: Option<PathBuf>,
+    pub modules: Option<Vec<String>>,
+    pub timeout: Duration,
+    pub probe_timeout: Duration,
+    /// Mount points shown by the disk module; empty shows every real one
+    pub mounts: Vec<String>,
+    /// Hide virtual interfaces in the network module
+    pub net_filter: bool,
+    /// List every hardware sensor, not only the CPU and GPU temperatures
+    pub sensors: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Config {
@@ -19,6 +38,13 @@ impl Config {
             json_output: false,
             help: false,
             version: false,
+            root: None,
+            modules: None,
+            timeout: Registry::DEFAULT_TIMEOUT,
+            probe_timeout: Registry::DEFAULT_PROBE_TIMEOUT,
+            mounts: Vec::new(),
+            net_filter: false,
+            sensors: false,
         }
     }
 
@@ -26,7 +52,8 @@ impl Config {
         let args: Vec<String> = env::args().collect();
         let mut config = Self::new();
 
-        for arg in args.iter().skip(1) {
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
             match arg.as_str() {
                 "-m" | "--minimal" => config.minimal = true,
                 "-n" | "--no-color" => config.no_color = true,
@@ -34,6 +61,19 @@ impl Config {
                 "-j" | "--json" => config.json_output = true,
                 "-h" | "--help" => config.help = true,
                 "-v" | "--version" => config.version = true,
+                "--net-filter" => config.net_filter = true,
+                "--sensors" => config.sensors = true,
+                "-M" | "--modules" | "-r" | "--root" | "--timeout" | "--probe-timeout"
+                | "--mounts" => {
+                    let result = match args.next() {
+                        Some(value) => config.set_value(arg, value),
+                        None => Err(format!("Missing value for argument: {}", arg)),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("{}", e);
+                        config.help = true;
+                    }
+                }
                 _ => {
                     eprintln!("Unknown argument: {}", arg);
                     config.help = true;
@@ -44,6 +84,31 @@ impl Config {
         config
     }
 
+    fn set_value(&mut self, arg: &str, value: &str) -> Result<(), String> {
+        match arg {
+            "-M" | "--modules" => self.modules = Some(Self::parse_list(value)),
+            "-r" | "--root" => self.root = Some(PathBuf::from(value)),
+            "--timeout" => self.timeout = Self::parse_millis(arg, value)?,
+            "--probe-timeout" => self.probe_timeout = Self::parse_millis(arg, value)?,
+            "--mounts" => self.mounts = Self::parse_list(value),
+            _ => unreachable!("{} does not take a value", arg),
+        }
+        Ok(())
+    }
+
+    fn parse_list(value: &str) -> Vec<String> {
+        value.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    fn parse_millis(arg: &str, value: &str) -> Result<Duration, String> {
+        value.parse()
+            .map(Duration::from_millis)
+            .map_err(|_| format!("Invalid milliseconds for {}: {}", arg, value))
+    }
+
     pub fn print_help() {
         println!("zfetch - Yet another system information fetcher for Linux");
         println!();
@@ -55,6 +120,19 @@ impl Config {
         println!("    -n, --no-color    Disable colored output");
         println!("    -l, --no-logo     Don't display the logo");
         println!("    -j, --json        Output information in JSON format");
+        println!("    -r, --root <DIR>  Inspect the system installed under DIR");
+        println!("    -M, --modules <LIST>");
+        println!("                      Only collect and show the comma separated modules");
+        println!("        --timeout <MS>");
+        println!("                      Overall deadline for collecting information (default {})",
+            Registry::DEFAULT_TIMEOUT.as_millis());
+        println!("        --probe-timeout <MS>");
+        println!("                      Deadline for each module (default {})",
+            Registry::DEFAULT_PROBE_TIMEOUT.as_millis());
+        println!("        --mounts <LIST>");
+        println!("                      Only show these mount points in the disk module");
+        println!("        --net-filter  Hide bridges, tunnels and other virtual interfaces");
+        println!("        --sensors     List every temperature, fan and voltage sensor");
         println!("    -h, --help        Show this help message");
         println!("    -v, --version     Show version information");
         println!();
@@ -63,6 +141,13 @@ impl Config {
         println!("    zfetch --minimal    # Show minimal information");
         println!("    zfetch --json       # Output as JSON");
         println!("    zfetch --no-color   # Disable colors");
+        println!("    zfetch --root /mnt  # Inspect a mounted system");
+        println!("    zfetch -M os,kernel # Only show OS and kernel");
+        println!();
+        println!("MODULES:");
+        for probe in Registry::builtin().probes() {
+            println!("    {:<22}{}", probe.name(), probe.label());
+        }
     }
 
     pub fn print_version() {
@@ -0,0 +1,155 @@
+//! Names of ARM and RISC-V cores, which /proc/cpuinfo only identifies by
+//! number (ARM) or by a devicetree compatible string (RISC-V).
+
+// MIDR implementer codes
+const IMPLEMENTERS: &[(u16, &str)] = &[
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x46, "Fujitsu"),
+    (0x48, "HiSilicon"),
+    (0x4e, "NVIDIA"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x61, "Apple"),
+    (0x6d, "Microsoft"),
+    (0xc0, "Ampere"),
+];
+
+// (implementer, part, name) for the cores found in boards and phones
+const PARTS: &[(u16, u16, &str)] = &[
+    (0x41, 0xc07, "Cortex-A7"),
+    (0x41, 0xc09, "Cortex-A9"),
+    (0x41, 0xc0d, "Cortex-A12"),
+    (0x41, 0xc0e, "Cortex-A17"),
+    (0x41, 0xc0f, "Cortex-A15"),
+    (0x41, 0xd01, "Cortex-A32"),
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd06, "Cortex-A65"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse-N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd0e, "Cortex-A76AE"),
+    (0x41, 0xd40, "Neoverse-V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd42, "Cortex-A78AE"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse-N2"),
+    (0x41, 0xd4a, "Neoverse-E1"),
+    (0x41, 0xd4b, "Cortex-A78C"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd4f, "Neoverse-V2"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0x41, 0xd87, "Cortex-A725"),
+    (0x41, 0xd85, "Cortex-X925"),
+    (0x4e, 0x004, "Carmel"),
+    (0x51, 0x800, "Kryo 2XX Gold"),
+    (0x51, 0x801, "Kryo 2XX Silver"),
+    (0x51, 0x802, "Kryo 3XX Gold"),
+    (0x51, 0x803, "Kryo 3XX Silver"),
+    (0x51, 0x804, "Kryo 4XX Gold"),
+    (0x51, 0x805, "Kryo 4XX Silver"),
+    (0x51, 0xc00, "Falkor"),
+    (0x51, 0x001, "Oryon"),
+    (0x61, 0x022, "Icestorm"),
+    (0x61, 0x023, "Firestorm"),
+    (0x61, 0x032, "Blizzard"),
+    (0x61, 0x033, "Avalanche"),
+    (0xc0, 0xac3, "Ampere-1"),
+    (0xc0, 0xac4, "Ampere-1a"),
+];
+
+// Compatible vendors of emulated boards, which name no SoC
+const GENERIC_VENDORS: &[&str] = &["linux", "qemu"];
+
+// Vendor prefixes of RISC-V "uarch" values
+const RISCV_VENDORS: &[(&str, &str)] = &[
+    ("sifive", "SiFive"),
+    ("thead", "T-Head"),
+    ("spacemit", "SpacemiT"),
+    ("starfive", "StarFive"),
+    ("andestech", "Andes"),
+];
+
+/// Name of an ARM core from the "CPU implementer" and "CPU part" fields,
+/// e.g. (0x41, 0xd0b) -> "Cortex-A76". Unknown parts keep their number.
+pub fn arm_core_name(implementer: u16, part: u16) -> String {
+    if let Some((_, _, name)) = PARTS.iter().find(|(i, p, _)| *i == implementer && *p == part) {
+        return name.to_string();
+    }
+
+    match IMPLEMENTERS.iter().find(|(i, _)| *i == implementer) {
+        Some((_, vendor)) => format!("{} 0x{:03x}", vendor, part),
+        None => format!("0x{:02x} 0x{:03x}", implementer, part),
+    }
+}
+
+/// "sifive,u74-mc" -> "SiFive U74-MC".
+pub fn riscv_core_name(uarch: &str) -> String {
+    let Some((vendor, core)) = uarch.split_once(',') else {
+        return uarch.to_string();
+    };
+    let vendor = RISCV_VENDORS.iter()
+        .find(|(prefix, _)| *prefix == vendor)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| vendor.to_string());
+    format!("{} {}", vendor, core.to_uppercase())
+}
+
+/// The SoC from the last devicetree compatible entry: "brcm,bcm2712" ->
+/// "BCM2712", "rockchip,rk3588" -> "RK3588". None for generic entries such
+/// as "linux,dummy-virt", which SoC part numbers are told apart from by
+/// their digits.
+pub fn soc_name(compatible: &str) -> Option<String> {
+    let soc = compatible.split('\0').rfind(|entry| !entry.is_empty())?;
+    let (vendor, model) = soc.split_once(',')?;
+    if GENERIC_VENDORS.contains(&vendor) || !model.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(model.to_uppercase())
+}
+
+/// Parses the "0x41" style numbers /proc/cpuinfo uses.
+pub fn parse_hex(value: &str) -> Option<u16> {
+    u16::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_soc_from_the_last_compatible() {
+        assert_eq!(soc_name("raspberrypi,5-model-b\0brcm,bcm2712\0").as_deref(), Some("BCM2712"));
+        assert_eq!(soc_name("radxa,rock-5b\0rockchip,rk3588\0").as_deref(), Some("RK3588"));
+    }
+
+    #[test]
+    fn generic_compatibles_name_no_soc() {
+        assert_eq!(soc_name("linux,dummy-virt\0"), None);
+        assert_eq!(soc_name("qemu,virt-9.0\0"), None);
+        assert_eq!(soc_name("arm,vexpress\0"), None);
+        assert_eq!(soc_name("riscv-virtio\0"), None);
+    }
+
+    #[test]
+    fn names_cores() {
+        assert_eq!(arm_core_name(0x41, 0xd0b), "Cortex-A76");
+        assert_eq!(arm_core_name(0x51, 0xfff), "Qualcomm 0xfff");
+        assert_eq!(arm_core_name(0x99, 0x001), "0x99 0x001");
+        assert_eq!(riscv_core_name("sifive,u74-mc"), "SiFive U74-MC");
+        assert_eq!(parse_hex("0xd0b"), Some(0xd0b));
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{SystemInfo, ascii};
+use crate::module::Entry;
+use crate::modules::{OsInfo, UptimeInfo, VirtInfo};
 
 pub struct Display {
     pub show_logo: bool,
@@ -10,7 +12,36 @@ const RESET: &str = "\x1b[0m";
 const CYAN: &str = "\x1b[36m";
 const WHITE: &str = "\x1b[37m";
 
+// Shown in place of a module that missed its deadline
+const TIMED_OUT: &str = "timed out";
+
+// Labels are right aligned to at least this width, so short outputs keep
+// their familiar shape
+const MIN_LABEL_WIDTH: usize = 20;
+
+// JSON keys that held plain strings before modules had structured values.
+// They keep the string; the structured value goes under "<key>_details".
+const STRING_KEYS: &[&str] = &["cpu_info", "gpu_info", "shell", "terminal", "desktop_environment"];
+
+impl Default for Display {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Display {
+    /// Modules collected for --minimal; user and hostname form the header.
+    pub const MINIMAL_MODULES: &'static [&'static str] = &[
+        "username",
+        "hostname",
+        "os_name",
+        "kernel_version",
+        "uptime",
+        "cpu_info",
+        "gpu_info",
+        "memory",
+    ];
+
     pub fn new() -> Self {
         Self {
             show_logo: true,
@@ -27,7 +58,7 @@ impl Display {
     }
 
     fn print_side_by_side_info(&self, info: &SystemInfo) {
-        let logo_lines = self.print_logo();
+        let logo_lines = self.print_logo(info);
         let info_lines = self.get_info_lines(info);
         
         let max_lines = logo_lines.len().max(info_lines.len());
@@ -57,23 +88,42 @@ impl Display {
     }
 
     fn get_info_lines(&self, info: &SystemInfo) -> Vec<String> {
-        vec![
-            self.format_info_line("User", &info.username, WHITE),
-            self.format_info_line("Hostname", &info.hostname, WHITE),
-            self.format_info_line("OS", &info.os_name, WHITE),
-            self.format_info_line("Kernel", &info.kernel_version, WHITE),
-            self.format_info_line("Uptime", &info.format_uptime(), WHITE),
-            self.format_info_line("Shell", &info.shell, WHITE),
-            self.format_info_line("Terminal", &info.terminal, WHITE),
-            self.format_info_line("Desktop Environment", &info.desktop_environment, WHITE),
-            self.format_info_line("CPU", &info.cpu_info, WHITE),
-            self.format_info_line("GPU", &info.gpu_info, WHITE),
-            self.format_info_line("Memory", &info.format_memory(), WHITE),
-        ]
-    }
-
-    fn format_info_line(&self, label: &str, value: &str, color: &str) -> String {
-        let formatted_label = format!("{:>20}: ", label);
+        let rows = self.info_rows(info);
+        let width = label_width(&rows);
+        rows.iter()
+            .map(|(label, value)| self.format_info_line(label, value, width, WHITE))
+            .collect()
+    }
+
+    /// Labelled rows of every collected module, in registry order.
+    fn info_rows(&self, info: &SystemInfo) -> Vec<(String, String)> {
+        let mut rows = Vec::new();
+
+        for entry in &info.entries {
+            if entry.timed_out {
+                rows.push((entry.label.to_string(), TIMED_OUT.to_string()));
+            } else if let Some(value) = &entry.value {
+                for (label, line) in value.rows(entry.label) {
+                    rows.push((label, self.host_value(info, entry, &line)));
+                }
+            }
+        }
+
+        rows
+    }
+
+    /// Marks values of host-only modules when inspecting another root, so
+    /// they are not mistaken for properties of the mounted system.
+    fn host_value(&self, info: &SystemInfo, entry: &Entry, value: &str) -> String {
+        if info.source.is_host() || !entry.host_only {
+            value.to_string()
+        } else {
+            format!("{} (host)", value)
+        }
+    }
+
+    fn format_info_line(&self, label: &str, value: &str, width: usize, color: &str) -> String {
+        let formatted_label = format!("{:>width$}: ", label, width = width);
         
         if self.show_colors {
             format!("{}{}{}{}{}{}", CYAN, formatted_label, RESET, color, value, RESET)
@@ -93,7 +143,7 @@ impl Display {
                 if chars.peek() == Some(&'[') {
                     chars.next(); // consume '['
                     // Skip until we find a letter (end of ANSI sequence)
-                    while let Some(c) = chars.next() {
+                    for c in chars.by_ref() {
                         if c.is_ascii_alphabetic() {
                             break;
                         }
@@ -106,14 +156,33 @@ impl Display {
         result
     }
 
-    fn print_logo(&self) -> Vec<String> {
-        // Try to get distribution-specific logo first
-        let logo = if let Some(distro_logo) = ascii::get_distro_logo(&self.get_os_name()) {
-            distro_logo
+    /// Logo for the container zfetch runs in, else for the distribution.
+    /// Modules that were not selected are not run for it: only the container
+    /// markers and os-release are read, never the hypervisor probes.
+    fn select_logo(&self, info: &SystemInfo) -> ascii::Logo {
+        // Inside a container the distribution is only the image's. An
+        // installed root is shown with its own logo, whatever zfetch runs in.
+        let container = if !info.source.is_host() {
+            None
+        } else if let Some(virt) = info.get::<VirtInfo>("virtualization") {
+            virt.container.clone()
         } else {
-            // Fallback to default logo
-            ascii::get_default_logo()
+            VirtInfo::detect_container(&info.source)
         };
+        if let Some(container) = container {
+            return ascii::get_container_logo(&container);
+        }
+
+        let id = match info.get::<OsInfo>("os_name") {
+            Some(os) => os.id.clone(),
+            None => OsInfo::read(&info.source).id,
+        };
+        // Fallback to default logo
+        ascii::get_distro_logo(&id).unwrap_or_else(ascii::get_default_logo)
+    }
+
+    fn print_logo(&self, info: &SystemInfo) -> Vec<String> {
+        let logo = self.select_logo(info);
 
         // Apply colors to logo lines
         if self.show_colors && !logo.colors.is_empty() {
@@ -137,88 +206,136 @@ impl Display {
         }
     }
 
-    fn get_os_name(&self) -> String {
-        // This is a simple way to get OS name for logo selection
-        std::fs::read_to_string("/etc/os-release")
-            .ok()
-            .and_then(|contents| {
-                contents.lines()
-                    .find(|line| line.starts_with("ID="))
-                    .map(|line| {
-                        let id = line.strip_prefix("ID=").unwrap_or("linux");
-                        // Remove quotes if present
-                        id.trim_matches('"').to_string()
-                    })
-            })
-            .unwrap_or_else(|| "linux".to_string())
-    }
-
     fn print_info_section(&self, info: &SystemInfo) {
         println!();
         
-        let info_data = [
-            ("User", &info.username, WHITE),
-            ("Hostname", &info.hostname, WHITE),
-            ("OS", &info.os_name, WHITE),
-            ("Kernel", &info.kernel_version, WHITE),
-            ("Uptime", &info.format_uptime(), WHITE),
-            ("Shell", &info.shell, WHITE),
-            ("Terminal", &info.terminal, WHITE),
-            ("Desktop Environment", &info.desktop_environment, WHITE),
-            ("CPU", &info.cpu_info, WHITE),
-            ("GPU", &info.gpu_info, WHITE),
-            ("Memory", &info.format_memory(), WHITE),
-        ];
-
-        for (label, value, color) in info_data {
-            self.print_info_line(label, value, color);
+        for line in self.get_info_lines(info) {
+            println!("{}", line);
         }
         
         println!();
     }
 
-    fn print_info_line(&self, label: &str, value: &str, color: &str) {
-        let formatted_label = format!("{:>20}: ", label);
-
-        if self.show_colors {
-            println!("{}{}{}{}{}{}", CYAN, formatted_label, RESET, color, value, RESET);
-        } else {
-            println!("{}{}", formatted_label, value);
+    pub fn print_minimal(&self, info: &SystemInfo) {
+        let summary = |name: &str| {
+            info.entry(name)
+                .and_then(|entry| entry.value.as_ref())
+                .map(|value| value.summary())
+        };
+        if let (Some(username), Some(hostname)) = (summary("username"), summary("hostname")) {
+            println!("{}@{}", username, hostname);
         }
-    }
 
-    pub fn print_minimal(&self, info: &SystemInfo) {
-        println!("{}@{}", info.username, info.hostname);
-        println!("OS: {}", info.os_name);
-        println!("Kernel: {}", info.kernel_version);
-        println!("Uptime: {}", info.format_uptime());
-        println!("CPU: {}", info.cpu_info);
-        println!("GPU: {}", info.gpu_info);
-        println!("Memory: {}", info.format_memory());
+        for entry in &info.entries {
+            if matches!(entry.name, "username" | "hostname") {
+                continue;
+            }
+            if entry.timed_out {
+                println!("{}: {}", entry.label, TIMED_OUT);
+            } else if let Some(value) = &entry.value {
+                println!("{}: {}", entry.label, self.host_value(info, entry, &value.summary()));
+            }
+        }
     }
 
     pub fn print_json(&self, info: &SystemInfo) -> Result<(), serde_json::Error> {
-        let json_output = serde_json::json!({
-            "username": info.username,
-            "hostname": info.hostname,
-            "os_name": info.os_name,
-            "kernel_version": info.kernel_version,
-            "uptime": info.uptime,
-            "uptime_formatted": info.format_uptime(),
-            "shell": info.shell,
-            "terminal": info.terminal,
-            "desktop_environment": info.desktop_environment,
-            "cpu_info": info.cpu_info,
-            "gpu_info": info.gpu_info,
-            "memory": {
-                "total": info.memory_info.total,
-                "used": info.memory_info.used,
-                "available": info.memory_info.available,
-                "formatted": info.format_memory()
+        let mut json_output = serde_json::Map::new();
+
+        for entry in &info.entries {
+            let Some(value) = &entry.value else {
+                continue;
+            };
+            if STRING_KEYS.contains(&entry.name) {
+                json_output.insert(entry.name.to_string(), serde_json::json!(value.summary()));
+                json_output.insert(format!("{}_details", entry.name), value.to_json());
+            } else {
+                json_output.insert(entry.name.to_string(), value.to_json());
             }
-        });
+        }
+
+        // "uptime" has always been seconds, with the text next to it
+        if let Some(uptime) = info.get::<UptimeInfo>("uptime") {
+            json_output.insert("uptime".to_string(), serde_json::json!(uptime.seconds));
+            json_output.insert("uptime_formatted".to_string(), serde_json::json!(uptime.format_uptime()));
+        }
+
+        let timed_out: Vec<&str> = info.entries.iter()
+            .filter(|entry| entry.timed_out)
+            .map(|entry| entry.name)
+            .collect();
+        if !timed_out.is_empty() {
+            json_output.insert("timed_out".to_string(), serde_json::json!(timed_out));
+        }
+
+        if !info.source.is_host() {
+            let host_only: Vec<&str> = info.entries.iter()
+                .filter(|entry| entry.host_only)
+                .map(|entry| entry.name)
+                .collect();
+            json_output.insert("root".to_string(), serde_json::json!(info.source.root()));
+            json_output.insert("host_only".to_string(), serde_json::json!(host_only));
+        }
 
         println!("{}", serde_json::to_string_pretty(&json_output)?);
         Ok(())
     }
 }
+
+/// Width that fits the longest label, such as "Disk (/mnt/backup)".
+fn label_width(rows: &[(String, String)]) -> usize {
+    rows.iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(MIN_LABEL_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::module::Entry;
+
+    #[test]
+    fn labels_align_to_the_longest_one() {
+        let display = Display { show_logo: false, show_colors: false };
+        let rows = [
+            ("OS".to_string(), "Arch Linux".to_string()),
+            ("Disk (/mnt/backup/photos)".to_string(), "1.0 GB / 2.0 GB (50%) - ext4".to_string()),
+        ];
+        let width = label_width(&rows);
+
+        let lines: Vec<String> = rows.iter()
+            .map(|(label, value)| display.format_info_line(label, value, width, WHITE))
+            .collect();
+        assert_eq!(lines[0].find(':'), lines[1].find(':'));
+        assert_eq!(label_width(&rows[..1]), MIN_LABEL_WIDTH);
+    }
+
+    fn os_entry(id: &str) -> Entry {
+        Entry {
+            name: "os_name",
+            label: "OS",
+            host_only: false,
+            timed_out: false,
+            value: Some(Box::new(OsInfo { name: id.to_string(), id: id.to_string() })),
+        }
+    }
+
+    #[test]
+    fn logo_reuses_the_collected_os() {
+        let display = Display { show_logo: true, show_colors: false };
+        let fixture = Fixture::new();
+        fixture.file("/etc/os-release", "NAME=\"Debian GNU/Linux\"\nID=debian\n");
+
+        // os-release is only read when the OS module did not run
+        let info = SystemInfo { source: fixture.source(), entries: vec![os_entry("arch")] };
+        assert_eq!(display.print_logo(&info), ascii::get_distro_logo("arch").unwrap().lines);
+        let info = SystemInfo { source: fixture.source(), entries: Vec::new() };
+        assert_eq!(display.print_logo(&info), ascii::get_distro_logo("debian").unwrap().lines);
+
+        // No os-release at all is plain Linux
+        let info = SystemInfo { source: Fixture::new().source(), entries: Vec::new() };
+        assert_eq!(display.print_logo(&info), ascii::get_distro_logo("linux").unwrap().lines);
+    }
+}
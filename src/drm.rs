@@ -0,0 +1,268 @@
+//! Modes currently set on DRM connectors, read with the KMS ioctls.
+//!
+//! sysfs lists the modes a connector supports but not the one in use; only
+//! the CRTC driving the connector knows that. The GET ioctls used here need
+//! no DRM master, just access to the card node, which logind grants to the
+//! user of the active seat.
+
+use std::fs::{File, OpenOptions};
+use std::mem::size_of;
+use std::os::unix::io::AsRawFd;
+use crate::Source;
+
+// drm_connector_enum_list in the kernel, indexed by connector type
+const CONNECTOR_TYPES: &[&str] = &[
+    "Unknown", "VGA", "DVI-I", "DVI-D", "DVI-A", "Composite", "SVIDEO", "LVDS",
+    "Component", "DIN", "DP", "HDMI-A", "HDMI-B", "TV", "eDP", "Virtual", "DSI",
+    "DPI", "Writeback", "SPI", "USB",
+];
+
+const MODE_FLAG_INTERLACE: u32 = 1 << 4;
+const MODE_FLAG_DBLSCAN: u32 = 1 << 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveMode {
+    pub width: u32,
+    pub height: u32,
+    /// Refresh rate in Hz
+    pub refresh_rate: Option<f64>,
+}
+
+/// Active modes of the connectors of a card node such as "card0", keyed by
+/// connector name as sysfs spells it ("DP-1", "eDP-1"). Empty when the node
+/// cannot be opened.
+pub fn active_modes(source: &Source, card: &str) -> Vec<(String, ActiveMode)> {
+    let path = source.path(format!("/dev/dri/{}", card));
+    let Ok(card) = OpenOptions::new().read(true).write(true).open(&path).or_else(|_| File::open(&path)) else {
+        return Vec::new();
+    };
+
+    connector_ids(&card)
+        .into_iter()
+        .filter_map(|id| {
+            let connector = get_connector(&card, id)?;
+            // The encoder is only set while a CRTC drives the connector
+            let crtc_id = get_encoder(&card, connector.encoder_id)?.crtc_id;
+            let crtc = get_crtc(&card, crtc_id)?;
+            if crtc.mode_valid == 0 {
+                return None;
+            }
+
+            let kind = CONNECTOR_TYPES.get(connector.connector_type as usize).unwrap_or(&"Unknown");
+            let name = format!("{}-{}", kind, connector.connector_type_id);
+            Some((name, active_mode(&crtc.mode)?))
+        })
+        .collect()
+}
+
+fn active_mode(mode: &ModeInfo) -> Option<ActiveMode> {
+    if mode.hdisplay == 0 || mode.vdisplay == 0 {
+        return None;
+    }
+
+    let total = mode.htotal as f64 * mode.vtotal as f64;
+    let mut refresh_rate = (total > 0.0).then(|| mode.clock as f64 * 1000.0 / total);
+    if let Some(rate) = refresh_rate.as_mut() {
+        if mode.flags & MODE_FLAG_INTERLACE != 0 {
+            *rate *= 2.0;
+        }
+        if mode.flags & MODE_FLAG_DBLSCAN != 0 {
+            *rate /= 2.0;
+        }
+        if mode.vscan > 1 {
+            *rate /= mode.vscan as f64;
+        }
+    }
+
+    Some(ActiveMode {
+        width: mode.hdisplay as u32,
+        height: mode.vdisplay as u32,
+        refresh_rate: refresh_rate.or((mode.vrefresh > 0).then_some(mode.vrefresh as f64)),
+    })
+}
+
+// Layouts from include/uapi/drm/drm_mode.h
+
+#[repr(C)]
+#[derive(Default)]
+struct CardResources {
+    fb_id_ptr: u64,
+    crtc_id_ptr: u64,
+    connector_id_ptr: u64,
+    encoder_id_ptr: u64,
+    count_fbs: u32,
+    count_crtcs: u32,
+    count_connectors: u32,
+    count_encoders: u32,
+    min_width: u32,
+    max_width: u32,
+    min_height: u32,
+    max_height: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct ModeInfo {
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    kind: u32,
+    name: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct GetConnector {
+    encoders_ptr: u64,
+    modes_ptr: u64,
+    props_ptr: u64,
+    prop_values_ptr: u64,
+    count_modes: u32,
+    count_props: u32,
+    count_encoders: u32,
+    encoder_id: u32,
+    connector_id: u32,
+    connector_type: u32,
+    connector_type_id: u32,
+    connection: u32,
+    mm_width: u32,
+    mm_height: u32,
+    subpixel: u32,
+    pad: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct GetEncoder {
+    encoder_id: u32,
+    encoder_type: u32,
+    crtc_id: u32,
+    possible_crtcs: u32,
+    possible_clones: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Crtc {
+    set_connectors_ptr: u64,
+    count_connectors: u32,
+    crtc_id: u32,
+    fb_id: u32,
+    x: u32,
+    y: u32,
+    gamma_size: u32,
+    mode_valid: u32,
+    mode: ModeInfo,
+}
+
+/// DRM_IOWR(nr, T): read-write ioctl of type 'd' with T's size.
+const fn iowr<T>(nr: u64) -> u64 {
+    (3 << 30) | ((size_of::<T>() as u64) << 16) | ((b'd' as u64) << 8) | nr
+}
+
+fn ioctl<T>(card: &File, nr: u64, argument: &mut T) -> bool {
+    loop {
+        // SAFETY: argument is a #[repr(C)] struct matching the layout the
+        // request encodes, and any pointers in it refer to live buffers of
+        // the sizes given by their counts
+        let result = unsafe { libc::ioctl(card.as_raw_fd(), iowr::<T>(nr) as _, argument as *mut T) };
+        if result == 0 {
+            return true;
+        }
+        let error = std::io::Error::last_os_error().raw_os_error();
+        if error != Some(libc::EINTR) && error != Some(libc::EAGAIN) {
+            return false;
+        }
+    }
+}
+
+fn connector_ids(card: &File) -> Vec<u32> {
+    let mut resources = CardResources::default();
+    if !ioctl(card, 0xa0, &mut resources) {
+        return Vec::new();
+    }
+
+    // Second call with room for the ids; the other arrays stay empty
+    let mut ids = vec![0u32; resources.count_connectors as usize];
+    let mut resources = CardResources {
+        connector_id_ptr: ids.as_mut_ptr() as u64,
+        count_connectors: ids.len() as u32,
+        ..CardResources::default()
+    };
+    if !ioctl(card, 0xa0, &mut resources) {
+        return Vec::new();
+    }
+    // A connector plugged in between the calls is left out
+    ids.truncate(resources.count_connectors as usize);
+    ids
+}
+
+fn get_connector(card: &File, connector_id: u32) -> Option<GetConnector> {
+    // A mode count of zero would make the kernel probe the connector,
+    // which can take long; room for one mode avoids that
+    let mut mode = ModeInfo::default();
+    let mut connector = GetConnector {
+        connector_id,
+        modes_ptr: &mut mode as *mut ModeInfo as u64,
+        count_modes: 1,
+        ..GetConnector::default()
+    };
+    (ioctl(card, 0xa7, &mut connector) && connector.encoder_id != 0).then_some(connector)
+}
+
+fn get_encoder(card: &File, encoder_id: u32) -> Option<GetEncoder> {
+    let mut encoder = GetEncoder { encoder_id, ..GetEncoder::default() };
+    (ioctl(card, 0xa6, &mut encoder) && encoder.crtc_id != 0).then_some(encoder)
+}
+
+fn get_crtc(card: &File, crtc_id: u32) -> Option<Crtc> {
+    let mut crtc = Crtc { crtc_id, ..Crtc::default() };
+    ioctl(card, 0xa1, &mut crtc).then_some(crtc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structs_match_the_kernel_layout() {
+        assert_eq!(size_of::<CardResources>(), 64);
+        assert_eq!(size_of::<ModeInfo>(), 68);
+        assert_eq!(size_of::<GetConnector>(), 80);
+        assert_eq!(size_of::<GetEncoder>(), 20);
+        assert_eq!(size_of::<Crtc>(), 104);
+        // DRM_IOCTL_MODE_GETRESOURCES and DRM_IOCTL_MODE_GETCRTC
+        assert_eq!(iowr::<CardResources>(0xa0), 0xc040_64a0);
+        assert_eq!(iowr::<Crtc>(0xa1), 0xc068_64a1);
+    }
+
+    #[test]
+    fn refresh_rate_comes_from_the_pixel_clock() {
+        // CEA 1920x1080@59.94
+        let mode = ModeInfo {
+            clock: 148_352,
+            hdisplay: 1920,
+            htotal: 2200,
+            vdisplay: 1080,
+            vtotal: 1125,
+            vrefresh: 60,
+            ..ModeInfo::default()
+        };
+        let active = active_mode(&mode).unwrap();
+        assert_eq!((active.width, active.height), (1920, 1080));
+        assert!((active.refresh_rate.unwrap() - 59.94).abs() < 0.01);
+
+        let interlaced = ModeInfo { flags: MODE_FLAG_INTERLACE, ..mode };
+        assert!((active_mode(&interlaced).unwrap().refresh_rate.unwrap() - 119.88).abs() < 0.01);
+    }
+}
@@ -0,0 +1,68 @@
+//! Throwaway directory trees standing in for a system root in tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::Source;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary root that probes can be pointed at. Paths passed to the
+/// builder methods are probe paths such as `/etc/os-release`. The tree is
+/// removed on drop.
+pub struct Fixture {
+    root: PathBuf,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "zfetch-fixture-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("create fixture root");
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn source(&self) -> Source {
+        Source::new(&self.root)
+    }
+
+    /// Path of a probe path inside the fixture, without following links.
+    pub fn real_path(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    pub fn file(&self, path: &str, contents: impl AsRef<[u8]>) -> &Self {
+        let path = self.real_path(path);
+        fs::create_dir_all(path.parent().unwrap()).expect("create fixture directory");
+        fs::write(path, contents).expect("write fixture file");
+        self
+    }
+
+    pub fn dir(&self, path: &str) -> &Self {
+        fs::create_dir_all(self.real_path(path)).expect("create fixture directory");
+        self
+    }
+
+    /// Symlink at `path` whose target is stored exactly as given, so
+    /// absolute targets point at the host unless the source confines them.
+    pub fn symlink(&self, path: &str, target: &str) -> &Self {
+        let path = self.real_path(path);
+        fs::create_dir_all(path.parent().unwrap()).expect("create fixture directory");
+        std::os::unix::fs::symlink(target, path).expect("create fixture symlink");
+        self
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
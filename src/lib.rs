@@ -2,7 +2,21 @@ pub mod system_info;
 pub mod display;
 pub mod config;
 pub mod ascii;
+pub mod source;
+pub mod module;
+pub mod modules;
+pub mod pci_ids;
+pub mod cpu_ids;
+pub mod sqlite;
+pub mod cgroup;
+pub mod smbios;
+pub mod drm;
 
 pub use system_info::SystemInfo;
 pub use display::Display;
 pub use config::Config;
+pub use source::Source;
+pub use module::{Module, ModuleValue, Registry};
+
+#[cfg(test)]
+mod fixture;
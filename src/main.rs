@@ -1,4 +1,4 @@
-use zfetch::{SystemInfo, Display, Config};
+use zfetch::{Display, Config, Registry, Source};
 
 fn main() {
     let config = Config::from_args();
@@ -14,7 +14,28 @@ fn main() {
     }
 
     // Gather system information
-    let system_info = SystemInfo::new();
+    let source = match &config.root {
+        Some(root) if !root.is_dir() => {
+            eprintln!("Root is not a directory: {}", root.display());
+            std::process::exit(1);
+        }
+        Some(root) => Source::installed(root),
+        None => Source::host(),
+    };
+
+    // Only run the modules that will actually be shown
+    let mut registry = Registry::from_config(&config);
+    if config.minimal && !config.json_output {
+        registry.select(Display::MINIMAL_MODULES)
+            .expect("minimal modules are built in");
+    }
+    if let Some(modules) = &config.modules {
+        if let Err(name) = registry.select(modules) {
+            eprintln!("Unknown module: {}", name);
+            std::process::exit(1);
+        }
+    }
+    let system_info = registry.collect(&source);
 
     // Configure display
     let mut display = Display::new();
@@ -0,0 +1,447 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::modules::*;
+use crate::{Config, Source, SystemInfo};
+
+/// A single piece of system information such as the kernel version or the
+/// memory usage. Implementations only know how to collect their value; the
+/// registry and `Display` take care of ordering and rendering.
+pub trait Module: Send + Sync {
+    type Value: ModuleValue;
+
+    /// Stable identifier, also used as the JSON key.
+    fn name(&self) -> &'static str;
+
+    /// Label shown in front of the value.
+    fn label(&self) -> &'static str;
+
+    /// Whether the value always describes the running host, even when
+    /// inspecting another root.
+    fn host_only(&self) -> bool {
+        false
+    }
+
+    /// Returns `None` when the module does not apply to this system.
+    fn collect(&self, ctx: &Context) -> Option<Self::Value>;
+}
+
+/// What a module works with while collecting.
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub source: Source,
+    /// Results arriving after this point are discarded.
+    pub deadline: Instant,
+    /// External commands still running, killed once the deadline passes.
+    children: Arc<Mutex<Vec<Child>>>,
+}
+
+impl Context {
+    pub fn new(source: Source, timeout: Duration) -> Self {
+        Self {
+            source,
+            deadline: Instant::now() + timeout,
+            children: Arc::default(),
+        }
+    }
+
+    /// Runs an external command and returns its stdout if it succeeded
+    /// before the deadline.
+    pub fn command_output(&self, program: &str, args: &[&str]) -> Option<String> {
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        // Drain stdout on another thread so a chatty command cannot block
+        // on a full pipe while we wait for it
+        let mut stdout = child.stdout.take()?;
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        // The child stays in the shared list until it has been reaped, so
+        // kill_children() can reach it and its pid cannot be reused
+        let id = child.id();
+        self.children.lock().ok()?.push(child);
+
+        loop {
+            let mut children = self.children.lock().ok()?;
+            // Gone means kill_children() already took care of it
+            let index = children.iter().position(|child| child.id() == id)?;
+            match children[index].try_wait() {
+                Ok(Some(_)) => {
+                    // Already reaped; wait() just hands back the status
+                    let status = children.swap_remove(index).wait().ok()?;
+                    if !status.success() {
+                        return None;
+                    }
+                    break;
+                }
+                Ok(None) if Instant::now() < self.deadline => {}
+                Ok(None) | Err(_) => {
+                    let mut child = children.swap_remove(index);
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+            }
+            drop(children);
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let output = reader.join().ok()?.ok()?;
+        Some(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Context for one module, with its own deadline but sharing the list
+    /// of running commands so `kill_children()` reaches all of them.
+    fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + timeout,
+            ..self.clone()
+        }
+    }
+
+    /// Kills every command still running, so none outlives zfetch.
+    pub fn kill_children(&self) {
+        if let Ok(mut children) = self.children.lock() {
+            for mut child in children.drain(..) {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+/// Gives access to the concrete type behind a `dyn ModuleValue`.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Value produced by a module, rendered by every output mode.
+pub trait ModuleValue: AsAny + Debug + Send + Sync + 'static {
+    /// One-line rendering, used by the minimal output.
+    fn summary(&self) -> String;
+
+    /// Labelled lines for the full output. Defaults to the summary shown
+    /// under the module label.
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        vec![(label.to_string(), self.summary())]
+    }
+
+    fn to_json(&self) -> serde_json::Value;
+}
+
+impl ModuleValue for String {
+    fn summary(&self) -> String {
+        self.clone()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
+
+/// Object-safe view of a `Module`, used to store modules with different
+/// value types side by side.
+pub trait Probe: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn label(&self) -> &'static str;
+    fn host_only(&self) -> bool;
+    fn probe(&self, ctx: &Context) -> Option<Box<dyn ModuleValue>>;
+}
+
+impl<M: Module> Probe for M {
+    fn name(&self) -> &'static str {
+        Module::name(self)
+    }
+
+    fn label(&self) -> &'static str {
+        Module::label(self)
+    }
+
+    fn host_only(&self) -> bool {
+        Module::host_only(self)
+    }
+
+    fn probe(&self, ctx: &Context) -> Option<Box<dyn ModuleValue>> {
+        self.collect(ctx)
+            .map(|value| Box::new(value) as Box<dyn ModuleValue>)
+    }
+}
+
+/// Ordered list of modules; the order is the display order.
+pub struct Registry {
+    probes: Vec<Arc<dyn Probe>>,
+    /// Overall deadline for a collection run.
+    pub timeout: Duration,
+    /// Deadline for each module; external commands are killed after it.
+    pub probe_timeout: Duration,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            probes: Vec::new(),
+            timeout: Self::DEFAULT_TIMEOUT,
+            probe_timeout: Self::DEFAULT_PROBE_TIMEOUT,
+        }
+    }
+}
+
+impl Registry {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(2000);
+    pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with every built-in module and default options.
+    pub fn builtin() -> Self {
+        Self::from_config(&Config::new())
+    }
+
+    /// Registry with every built-in module, configured from `config`.
+    pub fn from_config(config: &Config) -> Self {
+        let mut registry = Self::new();
+        registry.timeout = config.timeout;
+        registry.probe_timeout = config.probe_timeout;
+        registry
+            .register(UserModule)
+            .register(HostnameModule)
+            .register(HostModule)
+            .register(FirmwareModule)
+            .register(OsModule)
+            .register(KernelModule)
+            .register(VirtualizationModule)
+            .register(UptimeModule)
+            .register(PackagesModule)
+            .register(ShellModule)
+            .register(TerminalModule)
+            .register(DesktopModule)
+            .register(CpuModule)
+            .register(GpuModule)
+            .register(DisplaysModule)
+            .register(MemoryModule)
+            .register(DiskModule { mounts: config.mounts.clone() })
+            .register(BatteryModule);
+        // Reading every hwmon chip is only worth it when the sensors were
+        // asked for; the CPU and GPU lines read their own temperature
+        if config.sensors || config.modules.iter().flatten().any(|name| matches(&SensorsModule, name)) {
+            registry.register(SensorsModule);
+        }
+        registry.register(NetworkModule { hide_virtual: config.net_filter });
+        registry
+    }
+
+    pub fn register(&mut self, module: impl Module + 'static) -> &mut Self {
+        self.probes.push(Arc::new(module));
+        self
+    }
+
+    pub fn probes(&self) -> &[Arc<dyn Probe>] {
+        &self.probes
+    }
+
+    /// Keeps only the modules matching `names`, by name or label, so nothing
+    /// else gets collected. Fails with the first name that matches nothing.
+    pub fn select<S: AsRef<str>>(&mut self, names: &[S]) -> Result<(), String> {
+        if let Some(unknown) = names.iter()
+            .map(AsRef::as_ref)
+            .find(|name| !self.probes.iter().any(|probe| matches(probe.as_ref(), name)))
+        {
+            return Err(unknown.to_string());
+        }
+
+        self.probes.retain(|probe| {
+            names.iter().any(|name| matches(probe.as_ref(), name.as_ref()))
+        });
+        Ok(())
+    }
+
+    /// Runs every module on its own thread, each with `probe_timeout` to
+    /// finish, and waits at most `timeout` for all of them. Modules that
+    /// miss either deadline are left behind and reported as timed out.
+    pub fn collect(&self, source: &Source) -> SystemInfo {
+        let ctx = Context::new(source.clone(), self.timeout);
+        let (sender, receiver) = mpsc::channel();
+
+        let deadlines: Vec<Instant> = self.probes.iter()
+            .enumerate()
+            .map(|(index, probe)| {
+                let probe = Arc::clone(probe);
+                let ctx = ctx.with_timeout(self.probe_timeout);
+                let deadline = ctx.deadline;
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let _ = sender.send((index, probe.probe(&ctx)));
+                });
+                deadline
+            })
+            .collect();
+        drop(sender);
+
+        let mut values: Vec<Option<Option<Box<dyn ModuleValue>>>> =
+            self.probes.iter().map(|_| None).collect();
+        loop {
+            // Stop at the overall deadline, or once every module still
+            // running is past its own
+            let outstanding = deadlines.iter()
+                .zip(&values)
+                .filter(|(_, value)| value.is_none())
+                .map(|(&deadline, _)| deadline)
+                .max();
+            let Some(wait_until) = outstanding.map(|deadline| deadline.min(ctx.deadline)) else {
+                break;
+            };
+            let Some(remaining) = wait_until.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            match receiver.recv_timeout(remaining) {
+                // A late result counts as timed out like a missing one
+                Ok((index, value)) if Instant::now() <= deadlines[index] => values[index] = Some(value),
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        ctx.kill_children();
+
+        let entries = self.probes.iter()
+            .zip(values)
+            .map(|(probe, value)| Entry {
+                name: probe.name(),
+                label: probe.label(),
+                host_only: probe.host_only(),
+                timed_out: value.is_none(),
+                value: value.flatten(),
+            })
+            .collect();
+
+        SystemInfo {
+            source: source.clone(),
+            entries,
+        }
+    }
+}
+
+/// Whether `name` picks `probe` on the command line, by name or label.
+fn matches(probe: &dyn Probe, name: &str) -> bool {
+    probe.name().eq_ignore_ascii_case(name) || probe.label().eq_ignore_ascii_case(name)
+}
+
+/// Result of running one module.
+#[derive(Debug)]
+pub struct Entry {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub host_only: bool,
+    /// The module did not finish before its deadline.
+    pub timed_out: bool,
+    pub value: Option<Box<dyn ModuleValue>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sleeps, then returns its name, or nothing without a name.
+    struct Sleep(&'static str, Option<&'static str>, u64);
+
+    impl Module for Sleep {
+        type Value = String;
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn label(&self) -> &'static str {
+            self.0
+        }
+
+        fn collect(&self, _ctx: &Context) -> Option<String> {
+            thread::sleep(Duration::from_millis(self.2));
+            self.1.map(str::to_string)
+        }
+    }
+
+    fn collect(timeout: u64, probe_timeout: u64, modules: Vec<Sleep>) -> (SystemInfo, Duration) {
+        let mut registry = Registry::new();
+        registry.timeout = Duration::from_millis(timeout);
+        registry.probe_timeout = Duration::from_millis(probe_timeout);
+        for module in modules {
+            registry.register(module);
+        }
+
+        let start = Instant::now();
+        let info = registry.collect(&Source::host());
+        (info, start.elapsed())
+    }
+
+    #[test]
+    fn slow_modules_miss_their_own_deadline() {
+        let (info, elapsed) = collect(5000, 200, vec![
+            Sleep("fast", Some("fast"), 0),
+            Sleep("slow", Some("slow"), 1000),
+        ]);
+
+        assert_eq!(info.get::<String>("fast").map(String::as_str), Some("fast"));
+        let slow = info.entry("slow").unwrap();
+        assert!(slow.timed_out && slow.value.is_none());
+        assert!(elapsed < Duration::from_millis(1000), "waited {:?}", elapsed);
+    }
+
+    #[test]
+    fn overall_timeout_applies_when_shorter() {
+        let (info, elapsed) = collect(200, 5000, vec![
+            Sleep("fast", Some("fast"), 0),
+            Sleep("slow", Some("slow"), 1000),
+        ]);
+
+        assert!(!info.entry("fast").unwrap().timed_out);
+        assert!(info.entry("slow").unwrap().timed_out);
+        assert!(elapsed < Duration::from_millis(1000), "waited {:?}", elapsed);
+    }
+
+    #[test]
+    fn modules_without_a_value_are_not_timed_out() {
+        let (info, _) = collect(5000, 1000, vec![Sleep("empty", None, 0)]);
+
+        let empty = info.entry("empty").unwrap();
+        assert!(!empty.timed_out && empty.value.is_none());
+    }
+
+    #[test]
+    fn sensors_are_only_collected_when_asked_for() {
+        let has_sensors = |config: &Config| {
+            Registry::from_config(config).probes().iter().any(|probe| probe.name() == "sensors")
+        };
+
+        let mut config = Config::new();
+        assert!(!has_sensors(&config));
+        config.modules = Some(vec!["cpu".to_string(), "Sensor".to_string()]);
+        assert!(has_sensors(&config));
+        config.modules = None;
+        config.sensors = true;
+        assert!(has_sensors(&config));
+    }
+}
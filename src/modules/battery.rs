@@ -0,0 +1,275 @@
+use std::path::Path;
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+// New batteries can hold a little more than their design capacity; far
+// more than that is a firmware bug, not a healthy battery
+const MAX_HEALTH: u64 = 150;
+
+#[derive(Debug, Clone)]
+pub struct BatteryInfo {
+    pub name: String,
+    pub model: Option<String>,
+    /// Charge in percent
+    pub capacity: Option<u8>,
+    /// Charging, Discharging, Full or Not charging
+    pub status: Option<String>,
+    /// Full capacity relative to the design capacity, in percent
+    pub health: Option<u8>,
+    pub cycle_count: Option<u32>,
+    /// Battery of a peripheral such as a mouse (scope=Device)
+    pub peripheral: bool,
+}
+
+impl BatteryInfo {
+    fn display_name(&self) -> &str {
+        self.model.as_deref().unwrap_or(&self.name)
+    }
+
+    fn format_battery(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.capacity, &self.status) {
+            (Some(capacity), Some(status)) => parts.push(format!("{}% [{}]", capacity, status)),
+            (Some(capacity), None) => parts.push(format!("{}%", capacity)),
+            (None, Some(status)) => parts.push(status.clone()),
+            (None, None) => {}
+        }
+        if let Some(health) = self.health {
+            parts.push(format!("health {}%", health));
+        }
+        if let Some(cycles) = self.cycle_count {
+            parts.push(format!("{} cycles", cycles));
+        }
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PowerInfo {
+    pub batteries: Vec<BatteryInfo>,
+    /// None when the system has no AC adapter entry
+    pub ac_online: Option<bool>,
+}
+
+impl PowerInfo {
+    fn format_ac(&self) -> Option<&'static str> {
+        self.ac_online.map(|online| if online { "Connected" } else { "Disconnected" })
+    }
+}
+
+impl ModuleValue for PowerInfo {
+    fn summary(&self) -> String {
+        self.batteries.iter()
+            .map(|battery| {
+                if battery.peripheral {
+                    format!("{}: {}", battery.display_name(), battery.format_battery())
+                } else {
+                    battery.format_battery()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let system_batteries = self.batteries.iter().filter(|battery| !battery.peripheral).count();
+        let mut rows: Vec<(String, String)> = self.batteries.iter()
+            .map(|battery| {
+                // Only name system batteries when there is more than one
+                let label = if battery.peripheral || system_batteries > 1 {
+                    format!("{} ({})", label, battery.display_name())
+                } else {
+                    label.to_string()
+                };
+                (label, battery.format_battery())
+            })
+            .collect();
+        if let Some(ac) = self.format_ac() {
+            rows.push(("AC Adapter".to_string(), ac.to_string()));
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "batteries": self.batteries.iter().map(|battery| serde_json::json!({
+                "name": battery.name,
+                "model": battery.model,
+                "capacity": battery.capacity,
+                "status": battery.status,
+                "health": battery.health,
+                "cycle_count": battery.cycle_count,
+                "peripheral": battery.peripheral,
+                "formatted": battery.format_battery()
+            })).collect::<Vec<_>>(),
+            "ac_online": self.ac_online,
+            "formatted": self.summary()
+        })
+    }
+}
+
+pub struct BatteryModule;
+
+impl Module for BatteryModule {
+    type Value = PowerInfo;
+
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn label(&self) -> &'static str {
+        "Battery"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    /// Desktops have no battery and get no line at all, even when a
+    /// wireless mouse or keyboard reports one.
+    fn collect(&self, ctx: &Context) -> Option<PowerInfo> {
+        let source = &ctx.source;
+        let supplies = source.read_dir("/sys/class/power_supply").ok()?;
+        let mut batteries = Vec::new();
+        let mut ac_online = None;
+
+        for supply in &supplies {
+            let supply_type = source.read_trimmed(supply.join("type"));
+            match supply_type.as_deref() {
+                Some("Battery") => {
+                    if let Some(battery) = read_battery(source, supply) {
+                        batteries.push(battery);
+                    }
+                }
+                Some("Mains") => {
+                    let online = source.read_trimmed(supply.join("online")).as_deref() == Some("1");
+                    ac_online = Some(ac_online.unwrap_or(false) || online);
+                }
+                _ => {}
+            }
+        }
+
+        if batteries.iter().all(|battery| battery.peripheral) {
+            return None;
+        }
+        // System batteries first
+        batteries.sort_by_key(|battery| battery.peripheral);
+
+        Some(PowerInfo { batteries, ac_online })
+    }
+}
+
+fn read_battery(source: &Source, supply: &Path) -> Option<BatteryInfo> {
+    let read = |file: &str| source.read_trimmed(supply.join(file));
+    let read_number = |file: &str| read(file)?.parse::<u64>().ok();
+
+    // Absent on some peripherals and on batteries that are not plugged in
+    if read("present").as_deref() == Some("0") {
+        return None;
+    }
+
+    // Batteries report either energy (µWh) or charge (µAh)
+    let health = read_number("energy_full")
+        .zip(read_number("energy_full_design"))
+        .or_else(|| read_number("charge_full").zip(read_number("charge_full_design")))
+        .filter(|&(_, design)| design > 0)
+        .map(|(full, design)| full * 100 / design)
+        .filter(|health| (1..=MAX_HEALTH).contains(health))
+        .map(|health| health as u8);
+
+    Some(BatteryInfo {
+        name: supply.file_name()?.to_string_lossy().into_owned(),
+        model: read("model_name").filter(|model| !model.is_empty()),
+        capacity: read_number("capacity").map(|capacity| capacity.min(100) as u8),
+        status: read("status").filter(|status| status != "Unknown"),
+        health,
+        // Many batteries report 0 when they do not track cycles
+        cycle_count: read_number("cycle_count")
+            .filter(|&cycles| cycles > 0)
+            .map(|cycles| cycles as u32),
+        peripheral: read("scope").as_deref() == Some("Device"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    fn collect(fixture: &Fixture) -> Option<PowerInfo> {
+        BatteryModule.collect(&Context::new(fixture.source(), Duration::from_secs(1)))
+    }
+
+    fn mouse(fixture: &Fixture) {
+        fixture
+            .file("/sys/class/power_supply/hidpp_battery_0/type", "Battery\n")
+            .file("/sys/class/power_supply/hidpp_battery_0/scope", "Device\n")
+            .file("/sys/class/power_supply/hidpp_battery_0/model_name", "MX Master 3\n")
+            .file("/sys/class/power_supply/hidpp_battery_0/capacity", "80\n");
+    }
+
+    #[test]
+    fn reads_a_laptop_battery_and_peripherals() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/power_supply/AC/type", "Mains\n")
+            .file("/sys/class/power_supply/AC/online", "1\n")
+            .file("/sys/class/power_supply/BAT0/type", "Battery\n")
+            .file("/sys/class/power_supply/BAT0/present", "1\n")
+            .file("/sys/class/power_supply/BAT0/capacity", "57\n")
+            .file("/sys/class/power_supply/BAT0/status", "Charging\n")
+            .file("/sys/class/power_supply/BAT0/energy_full", "45000000\n")
+            .file("/sys/class/power_supply/BAT0/energy_full_design", "50000000\n")
+            .file("/sys/class/power_supply/BAT0/cycle_count", "312\n");
+        mouse(&fixture);
+
+        let power = collect(&fixture).unwrap();
+        assert_eq!(power.ac_online, Some(true));
+        assert_eq!(power.rows("Battery"), vec![
+            ("Battery".to_string(), "57% [Charging], health 90%, 312 cycles".to_string()),
+            ("Battery (MX Master 3)".to_string(), "80%".to_string()),
+            ("AC Adapter".to_string(), "Connected".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn health_comes_from_charge_when_energy_is_missing() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/power_supply/BAT1/type", "Battery\n")
+            .file("/sys/class/power_supply/BAT1/charge_full", "3800000\n")
+            .file("/sys/class/power_supply/BAT1/charge_full_design", "4000000\n")
+            .file("/sys/class/power_supply/BAT1/cycle_count", "0\n")
+            .file("/sys/class/power_supply/ADP1/type", "Mains\n")
+            .file("/sys/class/power_supply/ADP1/online", "0\n");
+
+        let power = collect(&fixture).unwrap();
+        assert_eq!(power.batteries[0].health, Some(95));
+        assert_eq!(power.batteries[0].cycle_count, None);
+        assert_eq!(power.format_ac(), Some("Disconnected"));
+    }
+
+    #[test]
+    fn bogus_health_is_left_out() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/power_supply/BAT0/type", "Battery\n")
+            .file("/sys/class/power_supply/BAT0/energy_full", "90000000\n")
+            .file("/sys/class/power_supply/BAT0/energy_full_design", "1000\n");
+
+        assert_eq!(collect(&fixture).unwrap().batteries[0].health, None);
+    }
+
+    #[test]
+    fn desktops_have_no_battery_line() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/power_supply/BAT0/type", "Battery\n")
+            .file("/sys/class/power_supply/BAT0/present", "0\n");
+        mouse(&fixture);
+
+        // An empty bay and a mouse are no system battery
+        assert!(collect(&fixture).is_none());
+    }
+}
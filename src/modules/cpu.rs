@@ -0,0 +1,588 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+use sysinfo::System;
+use crate::cgroup::{self, parse_cpu_list};
+use crate::cpu_ids;
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::sensors::{self, SensorKind};
+use crate::Source;
+
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+// Intel hybrid CPUs register one perf PMU per core type
+const INTEL_CORE_TYPES: &[&str] = &["/sys/devices/cpu_core/cpus", "/sys/devices/cpu_atom/cpus"];
+
+#[derive(Debug, Clone)]
+pub struct CacheInfo {
+    pub level: u8,
+    /// Data, Instruction or Unified
+    pub kind: String,
+    /// Size of one instance in bytes
+    pub size: u64,
+    /// Number of instances across the system
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CpuInfo {
+    /// Brand string, or the SoC on devicetree systems
+    pub model: String,
+    /// Board name from the devicetree, e.g. "Raspberry Pi 5 Model B"
+    pub board: Option<String>,
+    /// Core names and how many of each, fastest first; only known where
+    /// the kernel reports cores by number (ARM) or uarch (RISC-V)
+    pub core_names: Vec<(String, usize)>,
+    pub architecture: Option<String>,
+    pub sockets: usize,
+    /// Physical cores
+    pub cores: usize,
+    /// Logical CPUs online
+    pub threads: usize,
+    /// Physical cores of each kind, fastest first; empty unless the CPU
+    /// mixes core types (P/E cores, big.LITTLE)
+    pub core_types: Vec<usize>,
+    /// Highest current frequency of any CPU, in kHz
+    pub current_frequency: Option<u64>,
+    pub max_frequency: Option<u64>,
+    pub governor: Option<String>,
+    pub caches: Vec<CacheInfo>,
+    /// Package temperature in °C
+    pub temperature: Option<f64>,
+    /// CPUs the cgroup zfetch runs in may use, when fewer than `threads`
+    pub cpu_limit: Option<f64>,
+}
+
+impl CpuInfo {
+    /// "8P+16E" for two core types, "1+3+4" for three clusters.
+    fn format_core_types(&self) -> Option<String> {
+        match self.core_types[..] {
+            [] | [_] => None,
+            [performance, efficiency] => Some(format!("{}P+{}E", performance, efficiency)),
+            _ => Some(self.core_types.iter().map(usize::to_string).collect::<Vec<_>>().join("+")),
+        }
+    }
+
+    /// "4× Cortex-A76 + 4× Cortex-A55".
+    fn format_core_names(&self) -> Option<String> {
+        if self.core_names.is_empty() {
+            return None;
+        }
+        Some(
+            self.core_names.iter()
+                .map(|(name, count)| format!("{}× {}", count, name))
+                .collect::<Vec<_>>()
+                .join(" + ")
+        )
+    }
+
+    pub fn format_cpu(&self) -> String {
+        let mut topology = Vec::new();
+        if self.sockets > 1 {
+            topology.push(format!("{} sockets", self.sockets));
+        }
+        match self.format_core_names().or_else(|| self.format_core_types()) {
+            Some(core_types) => topology.push(core_types),
+            None if self.cores > 0 => topology.push(plural(self.cores, "core")),
+            None => {}
+        }
+        if self.threads > 0 && self.threads != self.cores {
+            topology.push(plural(self.threads, "thread"));
+        }
+
+        let name: Vec<&str> = self.board.iter()
+            .map(String::as_str)
+            .chain([self.model.as_str()])
+            .filter(|part| !part.is_empty())
+            .collect();
+        let mut cpu = name.join(" — ");
+        match (cpu.is_empty(), topology.is_empty()) {
+            (true, true) => cpu = "Unknown CPU".to_string(),
+            (true, false) => cpu = topology.join(", "),
+            (false, false) => cpu.push_str(&format!(" ({})", topology.join(", "))),
+            (false, true) => {}
+        }
+        if let Some(max) = self.max_frequency {
+            cpu.push_str(&format!(" @ {}", format_frequency(max)));
+        }
+        if let Some(temperature) = self.temperature {
+            cpu.push_str(&format!(" [{}]", sensors::format_reading(SensorKind::Temperature, temperature)));
+        }
+        if let Some(limit) = self.cpu_limit {
+            cpu.push_str(&format!(" — limit {} CPUs", format_cpus(limit)));
+        }
+        cpu
+    }
+
+    fn format_frequency(&self) -> Option<String> {
+        let mut frequency = match (self.current_frequency, self.max_frequency) {
+            (Some(current), Some(max)) => format!("{} / {}", format_frequency(current), format_frequency(max)),
+            (Some(frequency), None) | (None, Some(frequency)) => format_frequency(frequency),
+            (None, None) => return None,
+        };
+        if let Some(governor) = &self.governor {
+            frequency.push_str(&format!(" ({})", governor));
+        }
+        Some(frequency)
+    }
+
+    /// Total per level: "L1 1.4 MB, L2 32 MB, L3 36 MB".
+    fn format_caches(&self) -> Option<String> {
+        let mut levels: BTreeMap<u8, u64> = BTreeMap::new();
+        for cache in &self.caches {
+            *levels.entry(cache.level).or_default() += cache.size * cache.count as u64;
+        }
+        if levels.is_empty() {
+            return None;
+        }
+        Some(
+            levels.iter()
+                .map(|(level, size)| format!("L{} {}", level, format_cache_size(*size)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl ModuleValue for CpuInfo {
+    fn summary(&self) -> String {
+        self.format_cpu()
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let mut rows = vec![(label.to_string(), self.format_cpu())];
+        if let Some(architecture) = &self.architecture {
+            rows.push((format!("{} Architecture", label), architecture.clone()));
+        }
+        if let Some(frequency) = self.format_frequency() {
+            rows.push((format!("{} Frequency", label), frequency));
+        }
+        if let Some(caches) = self.format_caches() {
+            rows.push((format!("{} Cache", label), caches));
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.model,
+            "board": self.board,
+            "core_names": self.core_names.iter().map(|(name, count)| serde_json::json!({
+                "name": name,
+                "count": count
+            })).collect::<Vec<_>>(),
+            "architecture": self.architecture,
+            "sockets": self.sockets,
+            "cores": self.cores,
+            "threads": self.threads,
+            "core_types": self.core_types,
+            "current_frequency": self.current_frequency,
+            "max_frequency": self.max_frequency,
+            "governor": self.governor,
+            "temperature": self.temperature,
+            "cpu_limit": self.cpu_limit,
+            "caches": self.caches.iter().map(|cache| serde_json::json!({
+                "level": cache.level,
+                "type": cache.kind,
+                "size": cache.size,
+                "count": cache.count
+            })).collect::<Vec<_>>(),
+            "formatted": self.format_cpu()
+        })
+    }
+}
+
+pub struct CpuModule;
+
+impl Module for CpuModule {
+    type Value = CpuInfo;
+
+    fn name(&self) -> &'static str {
+        "cpu_info"
+    }
+
+    fn label(&self) -> &'static str {
+        "CPU"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<CpuInfo> {
+        let source = &ctx.source;
+        let cpus = online_cpus(source);
+
+        let cpuinfo = source.read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let board = devicetree_string(source, "/proc/device-tree/model");
+        let soc = devicetree_string(source, "/proc/device-tree/compatible")
+            .and_then(|compatible| cpu_ids::soc_name(&compatible));
+
+        let core_names = core_names(source, &cpuinfo);
+
+        let mut info = CpuInfo {
+            model: soc.unwrap_or_else(|| cpu_model(&cpuinfo, !core_names.is_empty())),
+            board,
+            core_names,
+            architecture: architecture(),
+            threads: cpus.len(),
+            ..CpuInfo::default()
+        };
+
+        let mut packages = BTreeSet::new();
+        let mut cores = BTreeSet::new();
+        for &cpu in &cpus {
+            let topology = format!("{}/cpu{}/topology", CPU_DIR, cpu);
+            let read = |file: &str| source.read_trimmed(format!("{}/{}", topology, file));
+            packages.insert(read("physical_package_id"));
+            // core_cpus_list replaced thread_siblings_list in Linux 5.7
+            let siblings = read("core_cpus_list").or_else(|| read("thread_siblings_list"));
+            cores.insert(siblings.unwrap_or_else(|| cpu.to_string()));
+
+            let cpufreq = |file: &str| {
+                source.read_trimmed(format!("{}/cpu{}/cpufreq/{}", CPU_DIR, cpu, file))?.parse::<u64>().ok()
+            };
+            info.current_frequency = info.current_frequency.max(cpufreq("scaling_cur_freq"));
+            info.max_frequency = info.max_frequency.max(cpufreq("cpuinfo_max_freq"));
+        }
+        info.sockets = packages.len();
+        info.cores = cores.len();
+        info.core_types = core_types(source, &cpus);
+        let first = cpus.first().copied().unwrap_or(0);
+        info.governor = source.read_trimmed(format!("{}/cpu{}/cpufreq/scaling_governor", CPU_DIR, first));
+        info.caches = caches(source, &cpus);
+        info.temperature = sensors::cpu_temperature(source);
+        info.cpu_limit = cgroup::Limits::read(source)
+            .cpus()
+            .filter(|&cpus| info.threads == 0 || cpus < info.threads as f64);
+
+        // Without sysfs (some containers) fall back to counting what the
+        // standard library reports
+        if info.threads == 0 {
+            info.threads = std::thread::available_parallelism().map(usize::from).unwrap_or(0);
+        }
+
+        Some(info)
+    }
+}
+
+/// "model name" from /proc/cpuinfo, as sysinfo would report it. Missing
+/// on most ARM and RISC-V systems, where the core names stand in for it
+/// rather than sysinfo's generic guess.
+fn cpu_model(cpuinfo: &str, has_core_names: bool) -> String {
+    if let Some(model) = cpuinfo_value(cpuinfo, "model name") {
+        return model;
+    }
+    if has_core_names {
+        return String::new();
+    }
+
+    let mut sys = System::new();
+    sys.refresh_cpu();
+    sys.cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .unwrap_or_default()
+}
+
+fn cpuinfo_value(cpuinfo: &str, key: &str) -> Option<String> {
+    cpuinfo.lines().find_map(|line| field_value(line, key))
+}
+
+fn field_value(line: &str, key: &str) -> Option<String> {
+    let (name, value) = line.split_once(':')?;
+    Some(value.trim().to_string()).filter(|value| name.trim() == key && !value.is_empty())
+}
+
+/// Names of the cores in each "processor" block of /proc/cpuinfo, counted
+/// and ordered by the scheduler's capacity so big cores come first.
+fn core_names(source: &Source, cpuinfo: &str) -> Vec<(String, usize)> {
+    // (name, capacity, count) in order of appearance
+    let mut groups: Vec<(String, u64, usize)> = Vec::new();
+
+    for block in cpuinfo.split("\n\n") {
+        let value = |key: &str| block.lines().find_map(|line| field_value(line, key));
+        let name = match (value("CPU implementer"), value("CPU part"), value("uarch")) {
+            (Some(implementer), Some(part), _) => {
+                match (cpu_ids::parse_hex(&implementer), cpu_ids::parse_hex(&part)) {
+                    (Some(implementer), Some(part)) => cpu_ids::arm_core_name(implementer, part),
+                    _ => continue,
+                }
+            }
+            (_, _, Some(uarch)) => cpu_ids::riscv_core_name(&uarch),
+            _ => continue,
+        };
+        let capacity = value("processor")
+            .and_then(|cpu| source.read_trimmed(format!("{}/cpu{}/cpu_capacity", CPU_DIR, cpu)))
+            .and_then(|capacity| capacity.parse().ok())
+            .unwrap_or(0);
+
+        match groups.iter_mut().find(|(group, _, _)| *group == name) {
+            Some((_, group_capacity, count)) => {
+                *group_capacity = (*group_capacity).max(capacity);
+                *count += 1;
+            }
+            None => groups.push((name, capacity, 1)),
+        }
+    }
+
+    groups.sort_by_key(|&(_, capacity, _)| std::cmp::Reverse(capacity));
+    groups.into_iter().map(|(name, _, count)| (name, count)).collect()
+}
+
+/// Devicetree properties are NUL terminated; compatible holds several.
+pub(crate) fn devicetree_string(source: &Source, path: &str) -> Option<String> {
+    let value = source.read(path).ok()?;
+    let value = String::from_utf8_lossy(&value).trim_end_matches('\0').trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+fn online_cpus(source: &Source) -> Vec<usize> {
+    if let Some(online) = source.read_trimmed(format!("{}/online", CPU_DIR)) {
+        return parse_cpu_list(&online);
+    }
+
+    // Offline CPUs have no topology directory
+    source.read_dir(CPU_DIR)
+        .unwrap_or_default()
+        .iter()
+        .filter(|path| source.exists(path.join("topology")))
+        .filter_map(|path| path.file_name()?.to_str()?.strip_prefix("cpu")?.parse().ok())
+        .collect()
+}
+
+/// Physical cores per core type, fastest first. Intel names its types in
+/// sysfs; on ARM the scheduler's cpu_capacity tells clusters apart.
+fn core_types(source: &Source, cpus: &[usize]) -> Vec<usize> {
+    let count_cores = |cpus: &[usize]| {
+        cpus.iter()
+            .map(|cpu| source.read_trimmed(format!("{}/cpu{}/topology/core_cpus_list", CPU_DIR, cpu)))
+            .collect::<BTreeSet<_>>()
+            .len()
+    };
+
+    let intel: Vec<usize> = INTEL_CORE_TYPES.iter()
+        .filter_map(|path| source.read_trimmed(path))
+        .map(|list| count_cores(&parse_cpu_list(&list)))
+        .filter(|&cores| cores > 0)
+        .collect();
+    if intel.len() > 1 {
+        return intel;
+    }
+
+    let mut clusters: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    for &cpu in cpus {
+        let capacity = source.read_trimmed(format!("{}/cpu{}/cpu_capacity", CPU_DIR, cpu))
+            .and_then(|capacity| capacity.parse().ok());
+        let Some(capacity) = capacity else {
+            return Vec::new();
+        };
+        clusters.entry(capacity).or_default().push(cpu);
+    }
+    if clusters.len() < 2 {
+        return Vec::new();
+    }
+    clusters.values().rev().map(|cpus| count_cores(cpus)).collect()
+}
+
+/// Every distinct cache instance, grouped by level, type and size; hybrid
+/// CPUs give each core type caches of its own size.
+fn caches(source: &Source, cpus: &[usize]) -> Vec<CacheInfo> {
+    // (level, type, size) -> shared_cpu_list of each instance
+    let mut found: BTreeMap<(u8, String, u64), BTreeSet<String>> = BTreeMap::new();
+
+    for &cpu in cpus {
+        let Ok(indexes) = source.read_dir(format!("{}/cpu{}/cache", CPU_DIR, cpu)) else {
+            continue;
+        };
+        for index in indexes {
+            let read = |file: &str| source.read_trimmed(index.join(file));
+            let (Some(level), Some(kind), Some(size)) = (
+                read("level").and_then(|level| level.parse().ok()),
+                read("type"),
+                read("size").and_then(|size| parse_cache_size(&size)),
+            ) else {
+                continue;
+            };
+            let shared = read("shared_cpu_list").unwrap_or_else(|| cpu.to_string());
+            found.entry((level, kind, size)).or_default().insert(shared);
+        }
+    }
+
+    found.into_iter()
+        .map(|((level, kind, size), instances)| CacheInfo { level, kind, size, count: instances.len() })
+        .collect()
+}
+
+/// "48K" -> 49152.
+fn parse_cache_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = match size.as_bytes().last()? {
+        b'K' => (&size[..size.len() - 1], 1024),
+        b'M' => (&size[..size.len() - 1], 1024 * 1024),
+        _ => (size, 1),
+    };
+    Some(number.parse::<u64>().ok()? * multiplier)
+}
+
+fn format_cache_size(bytes: u64) -> String {
+    const MEGABYTE: u64 = 1024 * 1024;
+    if bytes >= MEGABYTE && bytes % MEGABYTE == 0 {
+        format!("{} MB", bytes / MEGABYTE)
+    } else if bytes >= MEGABYTE {
+        format!("{:.1} MB", bytes as f64 / MEGABYTE as f64)
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+/// 2.0 -> "2", 1.5 -> "1.5".
+fn format_cpus(cpus: f64) -> String {
+    if cpus.fract() == 0.0 {
+        format!("{}", cpus)
+    } else {
+        format!("{:.1}", cpus)
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("1 {}", word)
+    } else {
+        format!("{} {}s", count, word)
+    }
+}
+
+/// kHz -> "4.70 GHz".
+fn format_frequency(khz: u64) -> String {
+    format!("{:.2} GHz", khz as f64 / 1_000_000.0)
+}
+
+/// Machine name from uname(2), which unlike the compile-time target
+/// reports aarch64 for a 32 bit build running on a 64 bit kernel.
+fn architecture() -> Option<String> {
+    let mut uts = MaybeUninit::<libc::utsname>::uninit();
+
+    // SAFETY: uname fills in the struct on success, and its fields are NUL
+    // terminated strings
+    unsafe {
+        if libc::uname(uts.as_mut_ptr()) != 0 {
+            return None;
+        }
+        let uts = uts.assume_init();
+        Some(CStr::from_ptr(uts.machine.as_ptr()).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    fn cpu_file(fixture: &Fixture, cpu: usize, file: &str, contents: impl std::fmt::Display) {
+        fixture.file(&format!("{}/cpu{}/{}", CPU_DIR, cpu, file), format!("{}\n", contents));
+    }
+
+    fn cache(fixture: &Fixture, cpu: usize, index: usize, level: u8, kind: &str, size: &str, shared: &str) {
+        for (file, contents) in [("level", level.to_string().as_str()), ("type", kind), ("size", size), ("shared_cpu_list", shared)] {
+            cpu_file(fixture, cpu, &format!("cache/index{}/{}", index, file), contents);
+        }
+    }
+
+    /// A Core i9-13900K: 8 P-cores with two threads each (cpu0-15) and 16
+    /// E-cores in clusters of four sharing an L2 (cpu16-31).
+    fn raptor_lake() -> Fixture {
+        let fixture = Fixture::new();
+        fixture
+            .file(&format!("{}/online", CPU_DIR), "0-31\n")
+            .file("/sys/devices/cpu_core/cpus", "0-15\n")
+            .file("/sys/devices/cpu_atom/cpus", "16-31\n")
+            .file("/proc/cpuinfo", "processor\t: 0\nmodel name\t: 13th Gen Intel(R) Core(TM) i9-13900K\n");
+
+        for cpu in 0..32 {
+            let performance = cpu < 16;
+            let siblings = if performance { format!("{}-{}", cpu & !1, cpu | 1) } else { cpu.to_string() };
+            cpu_file(&fixture, cpu, "topology/physical_package_id", 0);
+            cpu_file(&fixture, cpu, "topology/core_cpus_list", &siblings);
+            cpu_file(&fixture, cpu, "cpu_capacity", if performance { 1024 } else { 612 });
+            cpu_file(&fixture, cpu, "cpufreq/cpuinfo_max_freq", if performance { 5_800_000 } else { 4_300_000 });
+            cpu_file(&fixture, cpu, "cpufreq/scaling_cur_freq", if performance { 5_500_000 } else { 4_000_000 });
+            cpu_file(&fixture, cpu, "cpufreq/scaling_governor", "powersave");
+
+            if performance {
+                cache(&fixture, cpu, 0, 1, "Data", "48K", &siblings);
+                cache(&fixture, cpu, 1, 1, "Instruction", "32K", &siblings);
+                cache(&fixture, cpu, 2, 2, "Unified", "2048K", &siblings);
+            } else {
+                let first = cpu - (cpu - 16) % 4;
+                cache(&fixture, cpu, 0, 1, "Data", "32K", &siblings);
+                cache(&fixture, cpu, 1, 1, "Instruction", "64K", &siblings);
+                cache(&fixture, cpu, 2, 2, "Unified", "4096K", &format!("{}-{}", first, first + 3));
+            }
+            cache(&fixture, cpu, 3, 3, "Unified", "36864K", "0-31");
+        }
+        fixture
+    }
+
+    fn collect(fixture: &Fixture) -> CpuInfo {
+        CpuModule.collect(&Context::new(fixture.source(), Duration::from_secs(1))).unwrap()
+    }
+
+    #[test]
+    fn describes_a_hybrid_cpu() {
+        let fixture = raptor_lake();
+        let cpu = collect(&fixture);
+
+        assert_eq!((cpu.sockets, cpu.cores, cpu.threads), (1, 24, 32));
+        assert_eq!(cpu.core_types, vec![8, 16]);
+        let rows = cpu.rows("CPU");
+        let row = |label: &str| rows.iter().find(|(row, _)| row == label).map(|(_, value)| value.as_str());
+        assert_eq!(row("CPU"), Some("13th Gen Intel(R) Core(TM) i9-13900K (8P+16E, 32 threads) @ 5.80 GHz"));
+        assert_eq!(row("CPU Frequency"), Some("5.50 GHz / 5.80 GHz (powersave)"));
+        // 8 × (48K + 32K) + 16 × (32K + 64K) of L1, 8 × 2M + 4 × 4M of L2
+        assert_eq!(row("CPU Cache"), Some("L1 2.1 MB, L2 32 MB, L3 36 MB"));
+    }
+
+    #[test]
+    fn cpu_capacity_separates_clusters_without_intel_pmus() {
+        let fixture = raptor_lake();
+        fixture
+            .file("/sys/devices/cpu_core/cpus", "")
+            .file("/sys/devices/cpu_atom/cpus", "");
+        let cpus: Vec<usize> = (0..32).collect();
+        assert_eq!(core_types(&fixture.source(), &cpus), vec![8, 16]);
+    }
+
+    #[test]
+    fn names_arm_cores_fastest_first() {
+        let fixture = Fixture::new();
+        let mut cpuinfo = String::new();
+        for cpu in 0..8 {
+            let (part, capacity) = if cpu < 4 { ("0xd05", 414) } else { ("0xd0b", 1024) };
+            cpuinfo.push_str(&format!("processor\t: {}\nCPU implementer\t: 0x41\nCPU part\t: {}\n\n", cpu, part));
+            cpu_file(&fixture, cpu, "cpu_capacity", capacity);
+            cpu_file(&fixture, cpu, "topology/core_cpus_list", cpu);
+            cpu_file(&fixture, cpu, "topology/physical_package_id", 0);
+        }
+        fixture
+            .file(&format!("{}/online", CPU_DIR), "0-7\n")
+            .file("/proc/cpuinfo", cpuinfo)
+            .file("/proc/device-tree/model", "Radxa ROCK 5B\0")
+            .file("/proc/device-tree/compatible", "radxa,rock-5b\0rockchip,rk3588\0");
+
+        let cpu = collect(&fixture);
+        assert_eq!(cpu.core_types, vec![4, 4]);
+        assert_eq!(cpu.format_cpu(), "Radxa ROCK 5B — RK3588 (4× Cortex-A76 + 4× Cortex-A55)");
+    }
+
+    #[test]
+    fn parses_cache_sizes() {
+        assert_eq!(parse_cache_size("48K"), Some(48 * 1024));
+        assert_eq!(parse_cache_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size("K"), None);
+        assert_eq!(parse_cache_size(""), None);
+        assert_eq!(format_cache_size(36 * 1024 * 1024), "36 MB");
+        assert_eq!(format_cache_size(1536 * 1024), "1.5 MB");
+        assert_eq!(format_cache_size(640 * 1024), "640 KB");
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+// Process name -> desktop environment, for sessions started without
+// XDG_CURRENT_DESKTOP (startx, some display managers, over SSH)
+const DESKTOP_PROCESSES: &[(&str, &str)] = &[
+    ("gnome-shell", "GNOME"),
+    ("plasmashell", "KDE"),
+    ("xfce4-session", "XFCE"),
+    ("mate-session", "MATE"),
+    // comm is cut at 15 characters
+    ("cinnamon-sessio", "Cinnamon"),
+    ("lxqt-session", "LXQt"),
+    ("lxsession", "LXDE"),
+    ("budgie-panel", "Budgie"),
+    ("cosmic-session", "COSMIC"),
+];
+
+// DESKTOP_SESSION -> desktop environment, where the two differ
+const SESSION_NAMES: &[(&str, &str)] = &[
+    ("gnome", "GNOME"),
+    ("gnome-xorg", "GNOME"),
+    ("gnome-wayland", "GNOME"),
+    ("ubuntu", "GNOME"),
+    ("plasma", "KDE"),
+    ("plasmax11", "KDE"),
+    ("plasmawayland", "KDE"),
+    ("xfce", "XFCE"),
+    ("mate", "MATE"),
+    ("cinnamon", "Cinnamon"),
+    ("cinnamon2d", "Cinnamon"),
+    ("lxqt", "LXQt"),
+    ("budgie-desktop", "Budgie"),
+];
+
+// Process name -> window manager or compositor
+const WINDOW_MANAGERS: &[(&str, &str)] = &[
+    ("gnome-shell", "Mutter"),
+    ("mutter", "Mutter"),
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("xfwm4", "Xfwm4"),
+    ("marco", "Marco"),
+    ("cinnamon", "Muffin"),
+    ("budgie-wm", "Budgie WM"),
+    ("cosmic-comp", "cosmic-comp"),
+    ("sway", "Sway"),
+    ("Hyprland", "Hyprland"),
+    ("river", "river"),
+    ("niri", "niri"),
+    ("labwc", "labwc"),
+    ("wayfire", "Wayfire"),
+    ("weston", "Weston"),
+    ("dwl", "dwl"),
+    ("gamescope", "gamescope"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("dwm", "dwm"),
+    ("awesome", "awesome"),
+    ("xmonad-x86_64-l", "xmonad"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("qtile", "Qtile"),
+    ("openbox", "Openbox"),
+    ("fluxbox", "Fluxbox"),
+    ("icewm", "IceWM"),
+    ("jwm", "JWM"),
+    ("fvwm", "FVWM"),
+    ("fvwm3", "FVWM"),
+    ("spectrwm", "spectrwm"),
+    ("enlightenment", "Enlightenment"),
+    ("compiz", "Compiz"),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct DesktopInfo {
+    pub desktop: Option<String>,
+    pub version: Option<String>,
+    /// Window manager, or compositor on Wayland
+    pub wm: Option<String>,
+    /// x11, wayland or tty
+    pub session_type: String,
+}
+
+impl DesktopInfo {
+    fn format_desktop(&self) -> Option<String> {
+        let desktop = self.desktop.as_ref()?;
+        Some(match &self.version {
+            Some(version) => format!("{} {}", desktop, version),
+            None => desktop.clone(),
+        })
+    }
+}
+
+impl ModuleValue for DesktopInfo {
+    fn summary(&self) -> String {
+        let details: Vec<&str> = self.wm.iter()
+            .map(String::as_str)
+            .chain([self.session_type.as_str()])
+            .collect();
+        let desktop = self.format_desktop()
+            .unwrap_or_else(|| "Unknown".to_string());
+        format!("{} ({})", desktop, details.join(", "))
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let mut rows = Vec::new();
+        if let Some(desktop) = self.format_desktop() {
+            rows.push((label.to_string(), desktop));
+        }
+        if let Some(wm) = &self.wm {
+            rows.push(("WM".to_string(), wm.clone()));
+        }
+        rows.push(("Session".to_string(), self.session_type.clone()));
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "desktop": self.desktop,
+            "version": self.version,
+            "wm": self.wm,
+            "session_type": self.session_type,
+            "formatted": self.summary()
+        })
+    }
+}
+
+pub struct DesktopModule;
+
+impl Module for DesktopModule {
+    type Value = DesktopInfo;
+
+    fn name(&self) -> &'static str {
+        "desktop_environment"
+    }
+
+    fn label(&self) -> &'static str {
+        "Desktop Environment"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<DesktopInfo> {
+        let source = &ctx.source;
+        let processes = running_processes(source);
+        let running = |table: &[(&str, &'static str)]| {
+            table.iter()
+                .find(|(process, _)| processes.contains(*process))
+                .map(|(_, name)| name.to_string())
+        };
+
+        let desktop = desktop_from_env(|var| std::env::var(var).ok()).or_else(|| running(DESKTOP_PROCESSES));
+        let version = desktop.as_deref().and_then(|desktop| desktop_version(source, desktop));
+
+        Some(DesktopInfo {
+            desktop,
+            version,
+            wm: running(WINDOW_MANAGERS),
+            session_type: session_type(),
+        })
+    }
+}
+
+/// "ubuntu:GNOME" -> "GNOME", "X-Cinnamon" -> "Cinnamon". Display
+/// managers name the session instead, and SDDM gives the path of its
+/// .desktop file; those names are mapped where they differ.
+fn desktop_from_env(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let set = |name: &str| var(name).filter(|value| !value.is_empty());
+
+    if let Some(current) = set("XDG_CURRENT_DESKTOP") {
+        let desktop = current.split(':').next_back()?;
+        return Some(desktop.strip_prefix("X-").unwrap_or(desktop).to_string());
+    }
+
+    let session = set("DESKTOP_SESSION").or_else(|| set("GDMSESSION"))?;
+    let session = session.rsplit('/').next()?.trim_end_matches(".desktop");
+    Some(
+        SESSION_NAMES.iter()
+            .find(|(name, _)| *name == session)
+            .map(|(_, desktop)| desktop.to_string())
+            .unwrap_or_else(|| session.to_string())
+    )
+}
+
+fn session_type() -> String {
+    if let Ok(session_type) = std::env::var("XDG_SESSION_TYPE") {
+        if !session_type.is_empty() && session_type != "unspecified" {
+            return session_type;
+        }
+    }
+
+    let session_type = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        "wayland"
+    } else if std::env::var_os("DISPLAY").is_some() {
+        "x11"
+    } else {
+        "tty"
+    };
+    session_type.to_string()
+}
+
+fn running_processes(source: &Source) -> HashSet<String> {
+    let Ok(entries) = source.read_dir("/proc") else {
+        return HashSet::new();
+    };
+
+    entries.iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.bytes().all(|byte| byte.is_ascii_digit()))
+        })
+        .filter_map(|path| source.read_to_string(path.join("comm")).ok())
+        .map(|comm| comm.trim_end_matches('\n').to_string())
+        .collect()
+}
+
+fn desktop_version(source: &Source, desktop: &str) -> Option<String> {
+    match desktop {
+        "GNOME" => version_xml(source, "/usr/share/gnome/gnome-version.xml"),
+        "MATE" => version_xml(source, "/usr/share/mate-about/mate-version.xml"),
+        "KDE" => ["/usr/share/wayland-sessions/plasma.desktop", "/usr/share/xsessions/plasma.desktop"]
+            .iter()
+            .filter_map(|path| source.read_to_string(path).ok())
+            .find_map(|session| {
+                session.lines()
+                    .find_map(|line| line.strip_prefix("X-KDE-PluginInfo-Version="))
+                    .map(str::to_string)
+            }),
+        _ => None,
+    }
+}
+
+/// Version from the <platform>, <minor> and <micro> elements GNOME and
+/// MATE install for their about dialogs.
+fn version_xml(source: &Source, path: &str) -> Option<String> {
+    let contents = source.read_to_string(path).ok()?;
+    let element = |name: &str| {
+        let start = contents.find(&format!("<{}>", name))? + name.len() + 2;
+        let end = start + contents[start..].find('<')?;
+        Some(contents[start..end].trim().to_string()).filter(|value| !value.is_empty())
+    };
+
+    let parts: Vec<String> = ["platform", "minor", "micro"]
+        .iter()
+        .map_while(|name| element(name))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn desktop(vars: &[(&str, &str)]) -> Option<String> {
+        desktop_from_env(|name| {
+            vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn reads_the_current_desktop() {
+        assert_eq!(desktop(&[("XDG_CURRENT_DESKTOP", "ubuntu:GNOME")]).as_deref(), Some("GNOME"));
+        assert_eq!(desktop(&[("XDG_CURRENT_DESKTOP", "X-Cinnamon")]).as_deref(), Some("Cinnamon"));
+        assert_eq!(desktop(&[("XDG_CURRENT_DESKTOP", "KDE"), ("DESKTOP_SESSION", "plasmawayland")]).as_deref(), Some("KDE"));
+    }
+
+    #[test]
+    fn falls_back_to_the_session_name() {
+        assert_eq!(desktop(&[("XDG_CURRENT_DESKTOP", ""), ("DESKTOP_SESSION", "xfce")]).as_deref(), Some("XFCE"));
+        assert_eq!(desktop(&[("DESKTOP_SESSION", "/usr/share/wayland-sessions/plasma.desktop")]).as_deref(), Some("KDE"));
+        assert_eq!(desktop(&[("GDMSESSION", "gnome-xorg")]).as_deref(), Some("GNOME"));
+        assert_eq!(desktop(&[("DESKTOP_SESSION", "hyprland")]).as_deref(), Some("hyprland"));
+        assert_eq!(desktop(&[]), None);
+    }
+
+    #[test]
+    fn reads_gnome_and_mate_versions() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/usr/share/gnome/gnome-version.xml", "\
+<?xml version=\"1.0\"?>
+<gnome-version>
+  <platform>46</platform>
+  <minor>2</minor>
+  <micro></micro>
+  <distributor>Arch Linux</distributor>
+  <date>2024-05-25</date>
+</gnome-version>
+")
+            .file("/usr/share/mate-about/mate-version.xml", "\
+<mate-version>
+  <platform>1</platform>
+  <minor>26</minor>
+  <micro>2</micro>
+</mate-version>
+");
+
+        let source = fixture.source();
+        assert_eq!(desktop_version(&source, "GNOME").as_deref(), Some("46.2"));
+        assert_eq!(desktop_version(&source, "MATE").as_deref(), Some("1.26.2"));
+        assert_eq!(desktop_version(&source, "XFCE"), None);
+    }
+
+    #[test]
+    fn reads_the_plasma_version() {
+        let fixture = Fixture::new();
+        fixture.file("/usr/share/xsessions/plasma.desktop", "[Desktop Entry]\nExec=startplasma-x11\nX-KDE-PluginInfo-Version=6.1.3\n");
+        assert_eq!(desktop_version(&fixture.source(), "KDE").as_deref(), Some("6.1.3"));
+    }
+
+    #[test]
+    fn finds_desktops_and_window_managers_among_processes() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/812/comm", "cinnamon-sessio\n")
+            .file("/proc/901/comm", "cinnamon\n")
+            .file("/proc/self/comm", "zfetch\n");
+
+        let processes = running_processes(&fixture.source());
+        assert!(processes.contains("cinnamon-sessio") && processes.contains("cinnamon"));
+        assert!(!processes.contains("zfetch"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::gigabytes;
+
+// Filesystems without storage of their own
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs",
+    "devpts", "devtmpfs", "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nsfs",
+    "proc", "pstore", "ramfs", "rpc_pipefs", "securityfs", "selinuxfs", "squashfs",
+    "sysfs", "tmpfs", "tracefs", "fuse.gvfsd-fuse", "fuse.portal",
+];
+
+#[derive(Debug, Clone)]
+pub struct DiskInfo {
+    pub mount_point: String,
+    pub device: String,
+    pub filesystem: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+}
+
+impl DiskInfo {
+    /// Usage the way df computes it: blocks reserved for root count as
+    /// neither used nor available.
+    pub fn percentage(&self) -> u8 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            return 0;
+        }
+        (self.used as f64 / usable as f64 * 100.0).round() as u8
+    }
+
+    pub fn format_disk(&self) -> String {
+        format!(
+            "{:.1} GB / {:.1} GB ({}%) - {}",
+            gigabytes(self.used),
+            gigabytes(self.total),
+            self.percentage(),
+            self.filesystem
+        )
+    }
+}
+
+impl ModuleValue for Vec<DiskInfo> {
+    fn summary(&self) -> String {
+        self.iter()
+            .map(|disk| format!("{}: {}", disk.mount_point, disk.format_disk()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        self.iter()
+            .map(|disk| (format!("{} ({})", label, disk.mount_point), disk.format_disk()))
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter()
+            .map(|disk| serde_json::json!({
+                "mount_point": disk.mount_point,
+                "device": disk.device,
+                "filesystem": disk.filesystem,
+                "total": disk.total,
+                "used": disk.used,
+                "available": disk.available,
+                "percentage": disk.percentage(),
+                "formatted": disk.format_disk()
+            }))
+            .collect()
+    }
+}
+
+pub struct DiskModule {
+    /// Mount points to show; empty shows every real filesystem
+    pub mounts: Vec<String>,
+}
+
+impl Module for DiskModule {
+    type Value = Vec<DiskInfo>;
+
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn label(&self) -> &'static str {
+        "Disk"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<Vec<DiskInfo>> {
+        let mounts = ctx.source.read_to_string("/proc/self/mounts").ok()?;
+        let mut disks: Vec<DiskInfo> = Vec::new();
+
+        for line in mounts.lines() {
+            // device mount_point filesystem options dump pass
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [device, mount_point, filesystem, ..] = fields[..] else {
+                continue;
+            };
+            let mount_point = unescape_mount_path(mount_point);
+
+            if self.mounts.is_empty() {
+                if PSEUDO_FILESYSTEMS.contains(&filesystem) {
+                    continue;
+                }
+                // btrfs subvolumes and bind mounts repeat a device already shown
+                if disks.iter().any(|disk| disk.device == device) {
+                    continue;
+                }
+            } else if !self.mounts.contains(&mount_point) {
+                continue;
+            }
+
+            // The mount table is the running kernel's, so its mount points
+            // are too; with --root they are still the host's filesystems
+            let Some(stat) = statvfs(&ctx.source.runtime_path(&mount_point)) else {
+                continue;
+            };
+            let (total, used, available) = disk_usage(&stat);
+            if total == 0 {
+                continue;
+            }
+
+            disks.push(DiskInfo {
+                device: device.to_string(),
+                filesystem: filesystem.to_string(),
+                total,
+                used,
+                available,
+                mount_point,
+            });
+        }
+
+        // Keep the order of --mounts rather than the mount table's
+        if !self.mounts.is_empty() {
+            disks.sort_by_key(|disk| self.mounts.iter().position(|mount| *mount == disk.mount_point));
+        }
+
+        (!disks.is_empty()).then_some(disks)
+    }
+}
+
+/// The mount table escapes spaces, tabs, newlines and backslashes as octal.
+fn unescape_mount_path(path: &str) -> String {
+    path.replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+// The statvfs fields are 32 bit on some targets
+#[allow(clippy::unnecessary_cast)]
+fn disk_usage(stat: &libc::statvfs) -> (u64, u64, u64) {
+    let block_size = stat.f_frsize as u64;
+    (
+        stat.f_blocks as u64 * block_size,
+        (stat.f_blocks - stat.f_bfree) as u64 * block_size,
+        stat.f_bavail as u64 * block_size,
+    )
+}
+
+fn statvfs(path: &Path) -> Option<libc::statvfs> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: path is NUL terminated and stat is only read when the call
+    // succeeded and filled it in
+    unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        Some(stat.assume_init())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_usage_of_mount_points_under_the_runtime_root() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/self/mounts", "/dev/nvme0n1p2 /data\\040disk ext4 rw,relatime 0 0\n")
+            .dir("/data disk");
+        let module = DiskModule { mounts: vec!["/data disk".to_string()] };
+
+        let disks = module.collect(&Context::new(fixture.source(), Duration::from_secs(1))).unwrap();
+        assert_eq!(disks.len(), 1);
+        assert_eq!((disks[0].device.as_str(), disks[0].filesystem.as_str()), ("/dev/nvme0n1p2", "ext4"));
+        assert_eq!(disks[0].mount_point, "/data disk");
+        assert!(disks[0].total > 0);
+    }
+
+    #[test]
+    fn missing_mount_points_are_skipped() {
+        let fixture = Fixture::new();
+        fixture.file("/proc/self/mounts", "/dev/sda1 /gone ext4 rw 0 0\n");
+        let module = DiskModule { mounts: vec!["/gone".to_string()] };
+
+        assert!(module.collect(&Context::new(fixture.source(), Duration::from_secs(1))).is_none());
+    }
+}
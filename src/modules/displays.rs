@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::path::Path;
+use crate::drm::{self, ActiveMode};
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+// Offsets of the four 18 byte descriptors in the base block
+const DESCRIPTORS: [usize; 4] = [54, 72, 90, 108];
+const MONITOR_NAME_TAG: u8 = 0xfc;
+
+// PNP ids of common monitor and panel makers; others show the raw id
+const MANUFACTURERS: &[(&str, &str)] = &[
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Innolux"),
+    ("DEL", "Dell"),
+    ("EIZ", "EIZO"),
+    ("GSM", "LG"),
+    ("HWP", "HP"),
+    ("IVM", "Iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplayInfo {
+    /// Connector such as DP-1 or eDP-1
+    pub connector: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub refresh_rate: Option<f64>,
+    /// Set when the mode in use could not be read, so the resolution and
+    /// refresh rate are the monitor's preferred ones
+    pub preferred_mode: bool,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    /// Physical size in millimetres
+    pub width_mm: Option<u32>,
+    pub height_mm: Option<u32>,
+}
+
+impl DisplayInfo {
+    pub fn diagonal_inches(&self) -> Option<f64> {
+        let (width, height) = (self.width_mm? as f64, self.height_mm? as f64);
+        // Projectors and some TVs report 0
+        if width == 0.0 || height == 0.0 {
+            return None;
+        }
+        Some((width * width + height * height).sqrt() / 25.4)
+    }
+
+    fn format_display(&self) -> String {
+        let mut parts = Vec::new();
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("{}x{}", width, height));
+            if let Some(refresh_rate) = self.refresh_rate {
+                parts.push(format!("@ {:.0} Hz", refresh_rate));
+            }
+            if self.preferred_mode {
+                parts.push("(preferred)".to_string());
+            }
+        }
+        if let Some(diagonal) = self.diagonal_inches() {
+            parts.push(format!("[{:.0}\"]", diagonal));
+        }
+
+        if let Some(name) = self.name() {
+            parts.push(format!("- {}", name));
+        }
+        parts.join(" ")
+    }
+
+    /// "Dell U2720Q"; models often already start with the maker's name.
+    fn name(&self) -> Option<String> {
+        match (&self.manufacturer, &self.model) {
+            (Some(manufacturer), Some(model))
+                if !model.to_lowercase().starts_with(&manufacturer.to_lowercase()) =>
+            {
+                Some(format!("{} {}", manufacturer, model))
+            }
+            (_, Some(model)) => Some(model.clone()),
+            (manufacturer, None) => manufacturer.clone(),
+        }
+    }
+}
+
+impl ModuleValue for Vec<DisplayInfo> {
+    fn summary(&self) -> String {
+        self.iter()
+            .map(|display| format!("{}: {}", display.connector, display.format_display()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        self.iter()
+            .map(|display| (format!("{} ({})", label, display.connector), display.format_display()))
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter()
+            .map(|display| serde_json::json!({
+                "connector": display.connector,
+                "width": display.width,
+                "height": display.height,
+                "refresh_rate": display.refresh_rate,
+                "mode": if display.preferred_mode { "preferred" } else { "active" },
+                "manufacturer": display.manufacturer,
+                "model": display.model,
+                "width_mm": display.width_mm,
+                "height_mm": display.height_mm,
+                "diagonal_inches": display.diagonal_inches(),
+                "formatted": display.format_display()
+            }))
+            .collect()
+    }
+}
+
+pub struct DisplaysModule;
+
+impl Module for DisplaysModule {
+    type Value = Vec<DisplayInfo>;
+
+    fn name(&self) -> &'static str {
+        "displays"
+    }
+
+    fn label(&self) -> &'static str {
+        "Display"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    /// Reads DRM connectors from sysfs, so it works on a bare console as
+    /// well as under X11 or Wayland.
+    fn collect(&self, ctx: &Context) -> Option<Vec<DisplayInfo>> {
+        let source = &ctx.source;
+        let mut active_modes: HashMap<String, Vec<(String, ActiveMode)>> = HashMap::new();
+        let displays: Vec<DisplayInfo> = source.read_dir("/sys/class/drm").ok()?
+            .iter()
+            .filter_map(|path| read_connector(source, path, &mut active_modes))
+            .collect();
+
+        (!displays.is_empty()).then_some(displays)
+    }
+}
+
+fn read_connector(
+    source: &Source,
+    path: &Path,
+    active_modes: &mut HashMap<String, Vec<(String, ActiveMode)>>,
+) -> Option<DisplayInfo> {
+    // card0-DP-1, as opposed to the card0 and renderD128 device nodes
+    let name = path.file_name()?.to_str()?;
+    let (card, connector) = name.split_once('-')?;
+    if !card.starts_with("card") {
+        return None;
+    }
+
+    let status = source.read_to_string(path.join("status")).ok()?;
+    if status.trim() != "connected" {
+        return None;
+    }
+
+    let mut display = DisplayInfo { connector: connector.to_string(), ..DisplayInfo::default() };
+
+    // The first mode is the preferred one, used when the mode actually set
+    // cannot be read from the card node
+    let modes = source.read_to_string(path.join("modes")).unwrap_or_default();
+    if let Some((width, height)) = modes.lines().next().and_then(parse_mode) {
+        display.width = Some(width);
+        display.height = Some(height);
+    }
+
+    if let Ok(edid) = source.read(path.join("edid")) {
+        if edid.starts_with(&EDID_HEADER) {
+            apply_edid(&mut display, &edid);
+        }
+    }
+
+    let active = active_modes.entry(card.to_string())
+        .or_insert_with(|| drm::active_modes(source, card))
+        .iter()
+        .find(|(name, _)| name == connector)
+        .map(|(_, mode)| *mode);
+    match active {
+        Some(mode) => {
+            display.width = Some(mode.width);
+            display.height = Some(mode.height);
+            display.refresh_rate = mode.refresh_rate;
+        }
+        None => display.preferred_mode = display.width.is_some(),
+    }
+
+    Some(display)
+}
+
+/// "2560x1440" or "1920x1080i" -> (width, height).
+fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    let (width, height) = mode.split_once('x')?;
+    let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Fills in what the EDID base block knows: the maker, the model name,
+/// the physical size and the preferred timing's refresh rate.
+fn apply_edid(display: &mut DisplayInfo, edid: &[u8]) {
+    if edid.len() < 128 {
+        return;
+    }
+
+    // Three letters of five bits each, 'A' being 1
+    let id = u16::from_be_bytes([edid[8], edid[9]]);
+    let letters: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char)
+        .collect();
+    if letters.chars().all(|letter| letter.is_ascii_uppercase()) {
+        display.manufacturer = Some(
+            MANUFACTURERS.iter()
+                .find(|(code, _)| *code == letters)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or(letters)
+        );
+    }
+
+    // Size in centimetres; the detailed timing below is more precise
+    if edid[21] > 0 && edid[22] > 0 {
+        display.width_mm = Some(edid[21] as u32 * 10);
+        display.height_mm = Some(edid[22] as u32 * 10);
+    }
+
+    let mut preferred_timing = true;
+    for offset in DESCRIPTORS {
+        let descriptor = &edid[offset..offset + 18];
+        let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]) as u64 * 10_000;
+
+        if pixel_clock > 0 {
+            // Only the first detailed timing is the preferred mode
+            if preferred_timing {
+                apply_timing(display, descriptor, pixel_clock);
+                preferred_timing = false;
+            }
+        } else if descriptor[3] == MONITOR_NAME_TAG {
+            let name: String = descriptor[5..]
+                .iter()
+                .take_while(|&&byte| byte != b'\n')
+                .map(|&byte| byte as char)
+                .collect();
+            let name = name.trim();
+            if !name.is_empty() {
+                display.model = Some(name.to_string());
+            }
+        }
+    }
+}
+
+fn apply_timing(display: &mut DisplayInfo, timing: &[u8], pixel_clock: u64) {
+    let horizontal = timing[2] as u32 | ((timing[4] as u32 & 0xf0) << 4);
+    let horizontal_blank = timing[3] as u32 | ((timing[4] as u32 & 0x0f) << 8);
+    let vertical = timing[5] as u32 | ((timing[7] as u32 & 0xf0) << 4);
+    let vertical_blank = timing[6] as u32 | ((timing[7] as u32 & 0x0f) << 8);
+    let width_mm = timing[12] as u32 | ((timing[14] as u32 & 0xf0) << 4);
+    let height_mm = timing[13] as u32 | ((timing[14] as u32 & 0x0f) << 8);
+
+    if width_mm > 0 && height_mm > 0 {
+        display.width_mm = Some(width_mm);
+        display.height_mm = Some(height_mm);
+    }
+
+    // The timing is only the mode in use when it matches the resolution
+    let matches_mode = display.width.is_none_or(|width| width == horizontal)
+        && display.height.is_none_or(|height| height == vertical);
+    let total = (horizontal + horizontal_blank) as u64 * (vertical + vertical_blank) as u64;
+    if matches_mode && total > 0 {
+        display.width = Some(horizontal);
+        display.height = Some(vertical);
+        display.refresh_rate = Some(pixel_clock as f64 / total as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// Base block of a Dell monitor with a 2560x1440 detailed timing.
+    fn edid() -> Vec<u8> {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        // "DEL"
+        edid[8..10].copy_from_slice(&0x10acu16.to_be_bytes());
+        edid[21] = 60;
+        edid[22] = 34;
+        // 241.5 MHz, 2560 + 160 by 1440 + 41, 597x336 mm
+        edid[54..69].copy_from_slice(&[
+            0x56, 0x5e, 0x00, 0xa0, 0xa0, 0xa0, 0x29, 0x50, 0, 0, 0, 0, 0x55, 0x50, 0x21,
+        ]);
+        edid[72 + 3] = MONITOR_NAME_TAG;
+        edid[72 + 5..90].copy_from_slice(b"DELL U2720Q\n ");
+        edid
+    }
+
+    fn collect(fixture: &Fixture) -> Option<Vec<DisplayInfo>> {
+        DisplaysModule.collect(&Context::new(fixture.source(), std::time::Duration::from_secs(1)))
+    }
+
+    #[test]
+    fn reads_the_monitor_from_edid() {
+        let mut display = DisplayInfo { width: Some(2560), height: Some(1440), ..DisplayInfo::default() };
+        apply_edid(&mut display, &edid());
+
+        assert_eq!(display.manufacturer.as_deref(), Some("Dell"));
+        assert_eq!(display.model.as_deref(), Some("DELL U2720Q"));
+        assert_eq!((display.width_mm, display.height_mm), (Some(597), Some(336)));
+        assert!((display.refresh_rate.unwrap() - 59.95).abs() < 0.01);
+        assert_eq!(display.name().as_deref(), Some("DELL U2720Q"));
+    }
+
+    #[test]
+    fn timing_of_another_mode_is_not_applied() {
+        let mut display = DisplayInfo { width: Some(1920), height: Some(1080), ..DisplayInfo::default() };
+        apply_edid(&mut display, &edid());
+
+        assert_eq!((display.width, display.height), (Some(1920), Some(1080)));
+        assert_eq!(display.refresh_rate, None);
+    }
+
+    #[test]
+    fn labels_the_preferred_mode_without_a_card_node() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/drm/card0-DP-1/status", "connected\n")
+            .file("/sys/class/drm/card0-DP-1/modes", "2560x1440\n1920x1080\n")
+            .file("/sys/class/drm/card0-DP-1/edid", edid())
+            .file("/sys/class/drm/card0-HDMI-A-1/status", "disconnected\n")
+            .dir("/sys/class/drm/renderD128");
+
+        let displays = collect(&fixture).unwrap();
+        assert_eq!(displays.len(), 1);
+        assert_eq!(displays[0].connector, "DP-1");
+        assert!(displays[0].preferred_mode);
+        assert_eq!(displays[0].format_display(), "2560x1440 @ 60 Hz (preferred) [27\"] - DELL U2720Q");
+        assert_eq!(displays.to_json()[0]["mode"], "preferred");
+    }
+
+    #[test]
+    fn parses_interlaced_modes() {
+        assert_eq!(parse_mode("1920x1080i"), Some((1920, 1080)));
+        assert_eq!(parse_mode("garbage"), None);
+    }
+}
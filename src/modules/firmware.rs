@@ -0,0 +1,217 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::host::dmi_value;
+use crate::Source;
+
+const EFIVARS: &str = "/sys/firmware/efi/efivars";
+// EFI_GLOBAL_VARIABLE, owner of SecureBoot
+const GLOBAL_VARIABLE_GUID: &str = "8be4df61-93ca-11d2-aa0d-00e098032b8c";
+// Vendor GUID systemd-boot stores its variables under
+const LOADER_GUID: &str = "4a67b082-0a4c-41cf-b6c7-440b29bb8c4f";
+
+#[derive(Debug, Clone, Default)]
+pub struct FirmwareInfo {
+    pub vendor: Option<String>,
+    pub version: Option<String>,
+    /// Release date as the firmware reports it, usually MM/DD/YYYY
+    pub date: Option<String>,
+    /// Booted through UEFI rather than legacy BIOS
+    pub uefi: bool,
+    /// None when the SecureBoot variable is missing or unreadable
+    pub secure_boot: Option<bool>,
+    /// Boot loader that set LoaderInfo, e.g. "systemd-boot 255.4"
+    pub boot_loader: Option<String>,
+}
+
+impl FirmwareInfo {
+    fn format_firmware(&self) -> String {
+        let mut firmware = [&self.vendor, &self.version]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if firmware.is_empty() {
+            firmware = "Unknown".to_string();
+        }
+        if let Some(date) = &self.date {
+            firmware.push_str(&format!(" ({})", date));
+        }
+        firmware
+    }
+
+    fn format_boot_mode(&self) -> String {
+        if !self.uefi {
+            return "Legacy BIOS".to_string();
+        }
+        match self.secure_boot {
+            Some(true) => "UEFI, Secure Boot enabled".to_string(),
+            Some(false) => "UEFI, Secure Boot disabled".to_string(),
+            None => "UEFI".to_string(),
+        }
+    }
+}
+
+impl ModuleValue for FirmwareInfo {
+    fn summary(&self) -> String {
+        format!("{} — {}", self.format_firmware(), self.format_boot_mode())
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let mut rows = vec![
+            (label.to_string(), self.format_firmware()),
+            ("Boot Mode".to_string(), self.format_boot_mode()),
+        ];
+        if let Some(boot_loader) = &self.boot_loader {
+            rows.push(("Boot Loader".to_string(), boot_loader.clone()));
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "vendor": self.vendor,
+            "version": self.version,
+            "date": self.date,
+            "uefi": self.uefi,
+            "secure_boot": self.secure_boot,
+            "boot_loader": self.boot_loader,
+            "formatted": self.summary()
+        })
+    }
+}
+
+pub struct FirmwareModule;
+
+impl Module for FirmwareModule {
+    type Value = FirmwareInfo;
+
+    fn name(&self) -> &'static str {
+        "firmware"
+    }
+
+    fn label(&self) -> &'static str {
+        "Firmware"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<FirmwareInfo> {
+        let source = &ctx.source;
+        let uefi = source.exists("/sys/firmware/efi");
+        let firmware = FirmwareInfo {
+            vendor: dmi_value(source, "bios_vendor"),
+            version: dmi_value(source, "bios_version"),
+            date: dmi_value(source, "bios_date"),
+            uefi,
+            secure_boot: uefi
+                .then(|| efi_variable(source, "SecureBoot", GLOBAL_VARIABLE_GUID))
+                .flatten()
+                .and_then(|data| data.first().map(|&enabled| enabled == 1)),
+            boot_loader: uefi
+                .then(|| efi_variable(source, "LoaderInfo", LOADER_GUID))
+                .flatten()
+                .and_then(|data| utf16_string(&data)),
+        };
+
+        // Devicetree boards without UEFI have nothing to report
+        let known = firmware.uefi || firmware.vendor.is_some() || firmware.version.is_some();
+        known.then_some(firmware)
+    }
+}
+
+/// Data of an EFI variable, without the 4 byte attribute header efivarfs
+/// puts in front of it.
+fn efi_variable(source: &Source, name: &str, guid: &str) -> Option<Vec<u8>> {
+    let contents = source.read(format!("{}/{}-{}", EFIVARS, name, guid)).ok()?;
+    contents.get(4..).map(<[u8]>::to_vec)
+}
+
+/// NUL terminated UTF-16LE, the encoding systemd-boot uses for strings.
+fn utf16_string(data: &[u8]) -> Option<String> {
+    let units: Vec<u16> = data.chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    let string = String::from_utf16_lossy(&units);
+    let string = string.trim();
+    (!string.is_empty()).then(|| string.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    // EFI_VARIABLE_NON_VOLATILE | BOOTSERVICE_ACCESS | RUNTIME_ACCESS
+    const ATTRIBUTES: [u8; 4] = [0x07, 0, 0, 0];
+
+    fn variable(fixture: &Fixture, name: &str, guid: &str, data: &[u8]) {
+        fixture.file(&format!("{}/{}-{}", EFIVARS, name, guid), [&ATTRIBUTES[..], data].concat());
+    }
+
+    fn utf16(string: &str) -> Vec<u8> {
+        string.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn collect(fixture: &Fixture) -> Option<FirmwareInfo> {
+        FirmwareModule.collect(&Context::new(fixture.source(), Duration::from_secs(1)))
+    }
+
+    #[test]
+    fn reads_secure_boot_state() {
+        let fixture = Fixture::new();
+        fixture.dir("/sys/firmware/efi");
+        variable(&fixture, "SecureBoot", GLOBAL_VARIABLE_GUID, &[1]);
+        assert_eq!(collect(&fixture).unwrap().secure_boot, Some(true));
+
+        variable(&fixture, "SecureBoot", GLOBAL_VARIABLE_GUID, &[0]);
+        let firmware = collect(&fixture).unwrap();
+        assert_eq!(firmware.secure_boot, Some(false));
+        assert_eq!(firmware.format_boot_mode(), "UEFI, Secure Boot disabled");
+    }
+
+    #[test]
+    fn short_variables_have_no_data() {
+        let fixture = Fixture::new();
+        fixture.file(&format!("{}/SecureBoot-{}", EFIVARS, GLOBAL_VARIABLE_GUID), [0x06, 0]);
+        assert_eq!(efi_variable(&fixture.source(), "SecureBoot", GLOBAL_VARIABLE_GUID), None);
+
+        // Only the attributes: the variable exists but says nothing
+        fixture.dir("/sys/firmware/efi");
+        variable(&fixture, "SecureBoot", GLOBAL_VARIABLE_GUID, &[]);
+        assert_eq!(efi_variable(&fixture.source(), "SecureBoot", GLOBAL_VARIABLE_GUID), Some(Vec::new()));
+        assert_eq!(collect(&fixture).unwrap().format_boot_mode(), "UEFI");
+    }
+
+    #[test]
+    fn reads_the_boot_loader() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/sys/firmware/efi")
+            .file("/sys/class/dmi/id/bios_vendor", "American Megatrends International, LLC.\n")
+            .file("/sys/class/dmi/id/bios_version", "1.A0\n");
+        variable(&fixture, "LoaderInfo", LOADER_GUID, &[utf16("systemd-boot 255.4"), vec![0, 0]].concat());
+
+        let firmware = collect(&fixture).unwrap();
+        assert_eq!(firmware.boot_loader.as_deref(), Some("systemd-boot 255.4"));
+        assert_eq!(firmware.secure_boot, None);
+    }
+
+    #[test]
+    fn decodes_utf16_strings() {
+        assert_eq!(utf16_string(&[utf16("systemd-boot 255"), vec![0, 0], utf16("junk")].concat()).as_deref(),
+                   Some("systemd-boot 255"));
+        // Unterminated, with a stray odd byte at the end
+        assert_eq!(utf16_string(&[utf16("GRUB"), vec![0x41]].concat()).as_deref(), Some("GRUB"));
+        assert_eq!(utf16_string(&[0x41]), None);
+        assert_eq!(utf16_string(&[0, 0, 0x41, 0]), None);
+    }
+
+    #[test]
+    fn legacy_bios_without_dmi_reports_nothing() {
+        assert!(collect(&Fixture::new()).is_none());
+    }
+}
@@ -0,0 +1,487 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::gigabytes;
+use crate::modules::sensors::{self, SensorKind};
+use crate::pci_ids::{self, PciId, PciIds};
+use crate::Source;
+
+#[derive(Debug, Clone, Default)]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub model: String,
+    /// Board maker's variant, from the PCI subsystem ids
+    pub subsystem: Option<String>,
+    /// PCI address such as 0000:01:00.0
+    pub slot: Option<String>,
+    /// Bound kernel driver
+    pub driver: Option<String>,
+    pub driver_version: Option<String>,
+    /// Dedicated video memory in bytes, where the driver exposes it
+    pub vram_total: Option<u64>,
+    pub vram_used: Option<u64>,
+    /// None when the source of the information cannot tell
+    pub integrated: Option<bool>,
+    /// Temperature in °C
+    pub temperature: Option<f64>,
+}
+
+impl GpuInfo {
+    pub fn name(&self) -> String {
+        let mut name = if self.vendor.is_empty() {
+            self.model.clone()
+        } else {
+            format!("{} {}", self.vendor, self.model)
+        };
+        if let Some(subsystem) = &self.subsystem {
+            name.push_str(&format!(" ({})", subsystem));
+        }
+        name
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        self.integrated.map(|integrated| if integrated { "Integrated" } else { "Discrete" })
+    }
+
+    fn format_gpu(&self) -> String {
+        let temperature = self.temperature
+            .map(|temperature| sensors::format_reading(SensorKind::Temperature, temperature));
+        let details: Vec<String> = self.kind()
+            .map(str::to_string)
+            .into_iter()
+            .chain(temperature)
+            .collect();
+        if details.is_empty() {
+            self.name()
+        } else {
+            format!("{} [{}]", self.name(), details.join(", "))
+        }
+    }
+
+    fn format_driver(&self) -> Option<String> {
+        let driver = self.driver.as_ref()?;
+        Some(match &self.driver_version {
+            Some(version) => format!("{} {}", driver, version),
+            None => driver.clone(),
+        })
+    }
+
+    fn format_vram(&self) -> Option<String> {
+        let total = self.vram_total?;
+        Some(match self.vram_used {
+            Some(used) => format!("{:.1} GB / {:.1} GB", gigabytes(used), gigabytes(total)),
+            None => format!("{:.1} GB", gigabytes(total)),
+        })
+    }
+}
+
+impl ModuleValue for Vec<GpuInfo> {
+    fn summary(&self) -> String {
+        if self.is_empty() {
+            return "Unknown GPU".to_string();
+        }
+        self.iter().map(GpuInfo::format_gpu).collect::<Vec<_>>().join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        if self.is_empty() {
+            return vec![(label.to_string(), self.summary())];
+        }
+        let mut rows = Vec::new();
+        for gpu in self {
+            rows.push((label.to_string(), gpu.format_gpu()));
+            if let Some(driver) = gpu.format_driver() {
+                rows.push((format!("{} Driver", label), driver));
+            }
+            if let Some(vram) = gpu.format_vram() {
+                rows.push((format!("{} VRAM", label), vram));
+            }
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter()
+            .map(|gpu| serde_json::json!({
+                "vendor": gpu.vendor,
+                "model": gpu.model,
+                "subsystem": gpu.subsystem,
+                "slot": gpu.slot,
+                "driver": gpu.driver,
+                "driver_version": gpu.driver_version,
+                "vram_total": gpu.vram_total,
+                "vram_used": gpu.vram_used,
+                "integrated": gpu.integrated,
+                "temperature": gpu.temperature,
+                "formatted": gpu.format_gpu()
+            }))
+            .collect()
+    }
+}
+
+pub struct GpuModule;
+
+impl Module for GpuModule {
+    type Value = Vec<GpuInfo>;
+
+    fn name(&self) -> &'static str {
+        "gpu_info"
+    }
+
+    fn label(&self) -> &'static str {
+        "GPU"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<Vec<GpuInfo>> {
+        Some(gpus(ctx))
+    }
+}
+
+fn gpus(ctx: &Context) -> Vec<GpuInfo> {
+    let source = &ctx.source;
+
+    // Name display controllers from sysfs and pci.ids, no fork needed
+    let gpus = pci_gpus(source);
+    if !gpus.is_empty() {
+        return gpus;
+    }
+
+    // Try lspci next
+    if let Some(output_str) = ctx.command_output("lspci", &["-mm"]) {
+        let gpus: Vec<GpuInfo> = output_str.lines()
+            .filter(|line| {
+                line.to_lowercase().contains("vga") ||
+                line.to_lowercase().contains("3d") ||
+                line.to_lowercase().contains("display")
+            })
+            .filter_map(|line| {
+                // Extract GPU name from lspci output:
+                // 01:00.0 "VGA compatible controller" "Vendor" "Device" ...
+                let parts: Vec<&str> = line.split('"').collect();
+                let slot = Some(parts[0].trim().to_string()).filter(|slot| !slot.is_empty());
+                if parts.len() >= 6 {
+                    Some(GpuInfo {
+                        vendor: parts[3].to_string(),
+                        model: parts[5].to_string(),
+                        slot,
+                        ..GpuInfo::default()
+                    })
+                } else if parts.len() >= 2 {
+                    Some(GpuInfo { model: parts[1].to_string(), slot, ..GpuInfo::default() })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if !gpus.is_empty() {
+            return gpus;
+        }
+    }
+
+    // Try glxinfo as fallback, it only reports the active renderer
+    if let Some(output_str) = ctx.command_output("glxinfo", &["-B"]) {
+        for line in output_str.lines() {
+            if line.contains("OpenGL renderer string:") {
+                if let Some(gpu_name) = line.split(':').nth(1) {
+                    return vec![GpuInfo { model: gpu_name.trim().to_string(), ..GpuInfo::default() }];
+                }
+            }
+        }
+    }
+
+    // Try nvidia-smi for NVIDIA GPUs, one line per GPU
+    if let Some(output_str) = ctx.command_output(
+        "nvidia-smi",
+        &[
+            "--query-gpu=gpu_name,pci.bus_id,driver_version,memory.total,memory.used,temperature.gpu",
+            "--format=csv,noheader,nounits",
+        ],
+    ) {
+        let gpus: Vec<GpuInfo> = output_str.lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let field = |index: usize| {
+                    fields.get(index).copied().filter(|field| !field.is_empty())
+                };
+                // Memory is reported in MiB
+                let mebibytes = |index: usize| {
+                    field(index)?.parse::<u64>().ok().map(|mib| mib * 1024 * 1024)
+                };
+                Some(GpuInfo {
+                    model: field(0)?.to_string(),
+                    slot: field(1).map(str::to_lowercase),
+                    driver: Some("nvidia".to_string()),
+                    driver_version: field(2).map(str::to_string),
+                    vram_total: mebibytes(3),
+                    vram_used: mebibytes(4),
+                    temperature: field(5).and_then(|temperature| temperature.parse().ok()),
+                    ..GpuInfo::default()
+                })
+            })
+            .collect();
+        if !gpus.is_empty() {
+            return gpus;
+        }
+    }
+
+    // Try reading from /proc/driver/nvidia if available, one directory per GPU
+    if let Ok(entries) = source.read_dir("/proc/driver/nvidia/gpus") {
+        return entries.iter()
+            .map(|path| GpuInfo {
+                vendor: "NVIDIA".to_string(),
+                model: "GPU (details unavailable)".to_string(),
+                slot: path.file_name().map(|slot| slot.to_string_lossy().into_owned()),
+                driver: Some("nvidia".to_string()),
+                driver_version: driver_version(source, "nvidia"),
+                ..GpuInfo::default()
+            })
+            .collect();
+    }
+
+    Vec::new()
+}
+
+const INTEL: u16 = 0x8086;
+const NVIDIA: u16 = 0x10de;
+
+// PCI class 0x03xxxx: VGA, XGA, 3D and other display controllers
+const DISPLAY_CLASS_PREFIX: &str = "0x03";
+
+fn pci_gpus(source: &Source) -> Vec<GpuInfo> {
+    let Ok(devices) = source.read_dir("/sys/bus/pci/devices") else {
+        return Vec::new();
+    };
+    let mut pci_ids = None;
+    let mut gpus = Vec::new();
+    let mut boot_vga = Vec::new();
+    let mut classes = Vec::new();
+
+    for device in devices {
+        let Some(class) = source.read_to_string(device.join("class"))
+            .ok()
+            .filter(|class| class.starts_with(DISPLAY_CLASS_PREFIX))
+        else {
+            continue;
+        };
+
+        let (Some(vendor), Some(device_id)) = (
+            pci_ids::read_sysfs_id(source, device.join("vendor")),
+            pci_ids::read_sysfs_id(source, device.join("device")),
+        ) else {
+            continue;
+        };
+        let subsystem = pci_ids::read_sysfs_id(source, device.join("subsystem_vendor"))
+            .zip(pci_ids::read_sysfs_id(source, device.join("subsystem_device")));
+        let id = PciId { vendor, device: device_id, subsystem };
+
+        // Only read the (large) database once a GPU was actually found
+        let name = pci_ids.get_or_insert_with(|| PciIds::load(source)).lookup(&id);
+
+        let driver = source.read_link(device.join("driver"))
+            .ok()
+            .and_then(|driver| Some(driver.file_name()?.to_string_lossy().into_owned()));
+
+        gpus.push(GpuInfo {
+            vendor: name.vendor
+                .as_deref()
+                .map(short_vendor_name)
+                .unwrap_or_else(|| format!("Vendor {:04x}", vendor)),
+            model: name.device.unwrap_or_else(|| format!("Device {:04x}", device_id)),
+            subsystem: name.subsystem,
+            slot: device.file_name().map(|slot| slot.to_string_lossy().into_owned()),
+            driver_version: driver.as_deref().and_then(|driver| driver_version(source, driver)),
+            // Only amdgpu exposes these
+            vram_total: read_u64(source, device.join("mem_info_vram_total")),
+            vram_used: read_u64(source, device.join("mem_info_vram_used")),
+            temperature: device.file_name()
+                .and_then(|slot| sensors::pci_temperature(source, &slot.to_string_lossy())),
+            driver,
+            integrated: None,
+        });
+        boot_vga.push(source.read_trimmed(device.join("boot_vga")).as_deref() == Some("1"));
+        classes.push((class.trim().to_string(), vendor));
+    }
+
+    // On hybrid graphics the firmware boots from the integrated GPU, so
+    // boot_vga tells the two apart. A single GPU gives nothing to compare.
+    let boot_vga_decides = gpus.len() > 1 && boot_vga.iter().filter(|&&boot| boot).count() == 1;
+    for ((gpu, boot), (class, vendor)) in gpus.iter_mut().zip(boot_vga).zip(classes) {
+        gpu.integrated = if boot_vga_decides {
+            Some(boot)
+        } else {
+            integrated_by_device(&class, vendor, gpu.slot.as_deref())
+        };
+    }
+
+    gpus
+}
+
+/// Guess for GPUs boot_vga cannot tell apart. 3D controllers are the
+/// render-only discrete GPUs of hybrid laptops, and the vendor settles
+/// Intel and NVIDIA. Only then is the bus looked at: integrated GPUs
+/// usually sit on the CPU's root bus while cards hang off a bridge, but
+/// VMs and some platforms put cards on bus 0 too.
+fn integrated_by_device(class: &str, vendor: u16, slot: Option<&str>) -> Option<bool> {
+    if class.starts_with("0x0302") {
+        return Some(false);
+    }
+    // "0000:00:02.0" is domain, bus, device and function
+    let (bus, device) = slot?.split_once(':').map(|(_, rest)| rest)?.split_once(':')?;
+    match vendor {
+        // Intel's integrated GPU always takes device 2 on the root bus;
+        // Arc cards sit anywhere else
+        INTEL => Some(bus == "00" && device == "02.0"),
+        // NVIDIA's integrated GPUs are Tegra platform devices, not PCI
+        NVIDIA => Some(false),
+        _ => Some(bus == "00"),
+    }
+}
+
+/// "Advanced Micro Devices, Inc. [AMD/ATI]" -> "AMD/ATI",
+/// "NVIDIA Corporation" -> "NVIDIA".
+fn short_vendor_name(name: &str) -> String {
+    if let Some(short) = name.split_once('[').and_then(|(_, rest)| rest.split_once(']')) {
+        return short.0.to_string();
+    }
+    name.split_whitespace().next().unwrap_or(name).to_string()
+}
+
+/// Version of a kernel driver: out-of-tree modules carry one in sysfs, and
+/// the NVIDIA blob reports its own in procfs.
+fn driver_version(source: &Source, driver: &str) -> Option<String> {
+    if let Some(version) = source.read_trimmed(format!("/sys/module/{}/version", driver)) {
+        return Some(version);
+    }
+
+    if driver == "nvidia" {
+        // NVRM version: NVIDIA UNIX x86_64 Kernel Module  535.154.05  Thu Dec 28 ...
+        let contents = source.read_to_string("/proc/driver/nvidia/version").ok()?;
+        let line = contents.lines().find(|line| line.starts_with("NVRM version:"))?;
+        let version = line.split("Kernel Module").nth(1)?.split_whitespace().next()?;
+        return Some(version.to_string());
+    }
+
+    None
+}
+
+fn read_u64(source: &Source, path: impl AsRef<std::path::Path>) -> Option<u64> {
+    source.read_trimmed(path)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn add_gpu(fixture: &Fixture, slot: &str, class: &str, vendor: &str, boot_vga: bool) {
+        let device = format!("/sys/bus/pci/devices/{}", slot);
+        fixture
+            .file(&format!("{}/class", device), format!("{}\n", class))
+            .file(&format!("{}/vendor", device), format!("{}\n", vendor))
+            .file(&format!("{}/device", device), "0x1234\n")
+            .file(&format!("{}/boot_vga", device), if boot_vga { "1\n" } else { "0\n" });
+    }
+
+    fn integrated(fixture: &Fixture) -> Vec<(Option<String>, Option<bool>)> {
+        pci_gpus(&fixture.source())
+            .into_iter()
+            .map(|gpu| (gpu.slot, gpu.integrated))
+            .collect()
+    }
+
+    #[test]
+    fn reads_amdgpu_vram_and_driver_version() {
+        let fixture = Fixture::new();
+        add_gpu(&fixture, "0000:03:00.0", "0x030000", "0x1002", true);
+        fixture
+            .file("/sys/bus/pci/devices/0000:03:00.0/mem_info_vram_total", "17163091968\n")
+            .file("/sys/bus/pci/devices/0000:03:00.0/mem_info_vram_used", "1073741824\n")
+            .dir("/sys/bus/pci/drivers/amdgpu")
+            .symlink("/sys/bus/pci/devices/0000:03:00.0/driver", "../../../bus/pci/drivers/amdgpu")
+            .file("/sys/module/amdgpu/version", "6.7.0\n");
+
+        let gpus = pci_gpus(&fixture.source());
+        assert_eq!((gpus[0].vram_total, gpus[0].vram_used), (Some(17_163_091_968), Some(1 << 30)));
+        assert_eq!(gpus[0].format_vram().as_deref(), Some("1.0 GB / 16.0 GB"));
+        assert_eq!(gpus[0].format_driver().as_deref(), Some("amdgpu 6.7.0"));
+    }
+
+    #[test]
+    fn vram_is_unknown_without_amdgpu_files() {
+        let fixture = Fixture::new();
+        add_gpu(&fixture, "0000:00:02.0", "0x030000", "0x8086", true);
+        fixture.file("/sys/bus/pci/devices/0000:00:02.0/mem_info_vram_total", "garbage\n");
+
+        let gpus = pci_gpus(&fixture.source());
+        assert_eq!((gpus[0].vram_total, gpus[0].vram_used), (None, None));
+        assert_eq!(gpus[0].format_vram(), None);
+    }
+
+    #[test]
+    fn boot_vga_separates_hybrid_graphics() {
+        let fixture = Fixture::new();
+        add_gpu(&fixture, "0000:00:02.0", "0x030000", "0x8086", true);
+        add_gpu(&fixture, "0000:01:00.0", "0x030000", "0x10de", false);
+
+        assert_eq!(integrated(&fixture), [
+            (Some("0000:00:02.0".to_string()), Some(true)),
+            (Some("0000:01:00.0".to_string()), Some(false)),
+        ]);
+    }
+
+    #[test]
+    fn single_gpus_are_placed_by_bus() {
+        let laptop = Fixture::new();
+        add_gpu(&laptop, "0000:00:02.0", "0x030000", "0x8086", true);
+        assert_eq!(integrated(&laptop)[0].1, Some(true));
+
+        let desktop = Fixture::new();
+        add_gpu(&desktop, "0000:03:00.0", "0x030000", "0x1002", true);
+        assert_eq!(integrated(&desktop)[0].1, Some(false));
+    }
+
+    #[test]
+    fn vendor_comes_before_the_bus() {
+        // A VM with a passed through card next to its emulated adapter,
+        // both on bus 0 and neither marked as boot VGA
+        let vm = Fixture::new();
+        add_gpu(&vm, "0000:00:01.0", "0x030000", "0x1234", false);
+        add_gpu(&vm, "0000:00:05.0", "0x030000", "0x10de", false);
+        assert_eq!(integrated(&vm), [
+            (Some("0000:00:01.0".to_string()), Some(true)),
+            (Some("0000:00:05.0".to_string()), Some(false)),
+        ]);
+
+        // An Arc card on the root bus is no integrated GPU
+        let workstation = Fixture::new();
+        add_gpu(&workstation, "0000:00:03.0", "0x030000", "0x8086", true);
+        assert_eq!(integrated(&workstation)[0].1, Some(false));
+    }
+
+    #[test]
+    fn boot_vga_comes_before_the_bus() {
+        // Ryzen APUs put their GPU behind a bridge like any card
+        let fixture = Fixture::new();
+        add_gpu(&fixture, "0000:01:00.0", "0x030000", "0x1002", false);
+        add_gpu(&fixture, "0000:05:00.0", "0x030000", "0x1002", true);
+        assert_eq!(integrated(&fixture), [
+            (Some("0000:01:00.0".to_string()), Some(false)),
+            (Some("0000:05:00.0".to_string()), Some(true)),
+        ]);
+    }
+
+    #[test]
+    fn three_d_controllers_are_discrete_without_boot_vga() {
+        let fixture = Fixture::new();
+        // Both off: the firmware booted neither, e.g. a headless server
+        add_gpu(&fixture, "0000:00:02.0", "0x038000", "0x8086", false);
+        add_gpu(&fixture, "0000:00:04.0", "0x030200", "0x10de", false);
+
+        assert_eq!(integrated(&fixture), [
+            (Some("0000:00:02.0".to_string()), Some(true)),
+            (Some("0000:00:04.0".to_string()), Some(false)),
+        ]);
+    }
+}
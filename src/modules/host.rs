@@ -0,0 +1,218 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::cpu::devicetree_string;
+use crate::smbios::{Chassis, Smbios};
+use crate::Source;
+
+// Values firmware vendors leave in DMI fields they never filled in
+const PLACEHOLDERS: &[&str] = &[
+    "to be filled by o.e.m.",
+    "system product name",
+    "system manufacturer",
+    "system version",
+    "default string",
+    "not applicable",
+    "not specified",
+    "none",
+    "o.e.m.",
+    "oem",
+    "type1productconfigid",
+    "invalid",
+    "undefined",
+    "unknown",
+    "x.x",
+    "0123456789",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct HostInfo {
+    pub vendor: Option<String>,
+    pub product: Option<String>,
+    /// Lenovo keeps the marketing name here, e.g. "ThinkPad X1 Carbon Gen 9"
+    pub version: Option<String>,
+    pub board_vendor: Option<String>,
+    pub board_name: Option<String>,
+    /// Board name from the devicetree, on systems without DMI
+    pub model: Option<String>,
+    pub chassis: Option<Chassis>,
+}
+
+impl HostInfo {
+    fn format_host(&self) -> String {
+        let host = self.name();
+        match self.chassis {
+            Some(chassis) if chassis != Chassis::Other => format!("{} [{}]", host, chassis.name()),
+            _ => host,
+        }
+    }
+
+    fn name(&self) -> String {
+        if let Some(model) = &self.model {
+            return model.clone();
+        }
+
+        // Custom built desktops only name the motherboard
+        let (vendor, product) = match &self.product {
+            Some(product) => (&self.vendor, product),
+            None => match &self.board_name {
+                Some(board) => (&self.board_vendor, board),
+                None => return "Unknown".to_string(),
+            },
+        };
+
+        let mut host = match vendor {
+            Some(vendor) if !product.to_lowercase().starts_with(&vendor.to_lowercase()) => {
+                format!("{} {}", vendor, product)
+            }
+            _ => product.clone(),
+        };
+        if let Some(version) = self.version.as_ref().filter(|_| self.product.is_some()) {
+            host.push_str(&format!(" ({})", version));
+        }
+        host
+    }
+}
+
+impl ModuleValue for HostInfo {
+    fn summary(&self) -> String {
+        self.format_host()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "vendor": self.vendor,
+            "product": self.product,
+            "version": self.version,
+            "board_vendor": self.board_vendor,
+            "board_name": self.board_name,
+            "model": self.model,
+            "chassis": self.chassis.map(|chassis| chassis.name()),
+            "formatted": self.format_host()
+        })
+    }
+}
+
+pub struct HostModule;
+
+impl Module for HostModule {
+    type Value = HostInfo;
+
+    fn name(&self) -> &'static str {
+        "host"
+    }
+
+    fn label(&self) -> &'static str {
+        "Host"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<HostInfo> {
+        let source = &ctx.source;
+        let host = HostInfo {
+            vendor: dmi_value(source, "sys_vendor"),
+            product: dmi_value(source, "product_name"),
+            version: dmi_value(source, "product_version"),
+            board_vendor: dmi_value(source, "board_vendor"),
+            board_name: dmi_value(source, "board_name"),
+            model: None,
+            chassis: chassis(source),
+        };
+        if host.product.is_some() || host.board_name.is_some() {
+            return Some(host);
+        }
+
+        // ARM boards describe themselves in the devicetree instead
+        let model = devicetree_string(source, "/proc/device-tree/model")?;
+        Some(HostInfo { model: Some(model), ..host })
+    }
+}
+
+/// The chassis type is also in sysfs, which needs no root; the table is
+/// the fallback for kernels that leave it out.
+fn chassis(source: &Source) -> Option<Chassis> {
+    match dmi_value(source, "chassis_type").and_then(|code| code.parse().ok()) {
+        Some(code) => Chassis::from_code(code),
+        None => Smbios::read(source)?.chassis(),
+    }
+}
+
+pub(crate) fn dmi_value(source: &Source, field: &str) -> Option<String> {
+    let value = source.read_trimmed(format!("/sys/class/dmi/id/{}", field))?;
+    let placeholder = value.is_empty() || PLACEHOLDERS.contains(&value.to_lowercase().as_str());
+    (!placeholder).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    fn collect(fixture: &Fixture) -> Option<HostInfo> {
+        HostModule.collect(&Context::new(fixture.source(), Duration::from_secs(1)))
+    }
+
+    fn dmi(fixture: &Fixture, fields: &[(&str, &str)]) {
+        for (field, value) in fields {
+            fixture.file(&format!("/sys/class/dmi/id/{}", field), format!("{}\n", value));
+        }
+    }
+
+    #[test]
+    fn names_the_system_with_its_version() {
+        let fixture = Fixture::new();
+        dmi(&fixture, &[
+            ("sys_vendor", "LENOVO"),
+            ("product_name", "20XW0055GE"),
+            ("product_version", "ThinkPad X1 Carbon Gen 9"),
+            ("chassis_type", "10"),
+        ]);
+
+        assert_eq!(collect(&fixture).unwrap().summary(), "LENOVO 20XW0055GE (ThinkPad X1 Carbon Gen 9) [Laptop]");
+    }
+
+    #[test]
+    fn placeholders_fall_back_to_the_board() {
+        let fixture = Fixture::new();
+        dmi(&fixture, &[
+            ("sys_vendor", "System manufacturer"),
+            ("product_name", "System Product Name"),
+            ("product_version", "To Be Filled By O.E.M."),
+            ("board_vendor", "ASUSTeK COMPUTER INC."),
+            ("board_name", "ROG STRIX B550-F GAMING"),
+            ("chassis_type", "3"),
+        ]);
+
+        let host = collect(&fixture).unwrap();
+        assert_eq!((&host.vendor, &host.product, &host.version), (&None, &None, &None));
+        assert_eq!(host.summary(), "ASUSTeK COMPUTER INC. ROG STRIX B550-F GAMING [Desktop]");
+    }
+
+    #[test]
+    fn every_placeholder_is_dropped() {
+        let fixture = Fixture::new();
+        for (index, placeholder) in ["Default string", "O.E.M.", "  None ", "x.x", "0123456789", ""].iter().enumerate() {
+            let field = format!("field{}", index);
+            dmi(&fixture, &[(&field, placeholder)]);
+            assert_eq!(dmi_value(&fixture.source(), &field), None, "{:?}", placeholder);
+        }
+    }
+
+    #[test]
+    fn boards_without_dmi_use_the_devicetree_model() {
+        let fixture = Fixture::new();
+        fixture.file("/proc/device-tree/model", "Raspberry Pi 5 Model B Rev 1.0\0");
+        dmi(&fixture, &[("product_name", "Default string")]);
+
+        let host = collect(&fixture).unwrap();
+        assert_eq!(host.model.as_deref(), Some("Raspberry Pi 5 Model B Rev 1.0"));
+        assert_eq!(host.summary(), "Raspberry Pi 5 Model B Rev 1.0");
+    }
+
+    #[test]
+    fn nothing_to_report_without_dmi_or_devicetree() {
+        assert!(collect(&Fixture::new()).is_none());
+    }
+}
@@ -0,0 +1,24 @@
+use crate::module::{Context, Module};
+
+pub struct HostnameModule;
+
+impl Module for HostnameModule {
+    type Value = String;
+
+    fn name(&self) -> &'static str {
+        "hostname"
+    }
+
+    fn label(&self) -> &'static str {
+        "Hostname"
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<String> {
+        Some(
+            ctx.source.read_to_string("/etc/hostname")
+                .unwrap_or_else(|_| "unknown".to_string())
+                .trim()
+                .to_string()
+        )
+    }
+}
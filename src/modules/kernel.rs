@@ -0,0 +1,85 @@
+use crate::module::{Context, Module};
+use crate::Source;
+
+pub struct KernelModule;
+
+impl Module for KernelModule {
+    type Value = String;
+
+    fn name(&self) -> &'static str {
+        "kernel_version"
+    }
+
+    fn label(&self) -> &'static str {
+        "Kernel"
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<String> {
+        let source = &ctx.source;
+        if !source.is_host() {
+            return Some(installed_kernels(source));
+        }
+
+        Some(
+            source.read_to_string("/proc/version")
+                .unwrap_or_else(|_| "unknown".to_string())
+                .split_whitespace()
+                .nth(2)
+                .unwrap_or("unknown")
+                .to_string()
+        )
+    }
+}
+
+/// Kernels installed in a mounted system, newest first. /proc/version
+/// would describe the running host instead.
+fn installed_kernels(source: &Source) -> String {
+    let mut kernels: Vec<String> = ["/usr/lib/modules", "/lib/modules"]
+        .iter()
+        .filter_map(|dir| source.read_dir(dir).ok())
+        .flatten()
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .collect();
+    kernels.sort_by_cached_key(|kernel| (version_key(kernel), kernel.clone()));
+    kernels.dedup();
+    kernels.reverse();
+
+    if kernels.is_empty() {
+        "unknown".to_string()
+    } else {
+        kernels.join(", ")
+    }
+}
+
+/// Numeric parts of a release such as "6.10.2-arch1-1", so that 6.10
+/// sorts above 6.9.
+fn version_key(release: &str) -> Vec<u64> {
+    release.split(['.', '-'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn lists_installed_kernels_newest_first() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/usr/lib/modules/6.9.1")
+            .dir("/usr/lib/modules/6.10.2")
+            .dir("/usr/lib/modules/5.4.0")
+            .dir("/lib/modules/6.10.2");
+
+        assert_eq!(installed_kernels(&Source::installed(fixture.root())), "6.10.2, 6.9.1, 5.4.0");
+    }
+
+    #[test]
+    fn compares_release_numbers() {
+        assert!(version_key("5.15.0-91-generic") > version_key("5.4.0-150-generic"));
+        assert!(version_key("6.10.2-arch1-1") > version_key("6.9.7-arch1-1"));
+        assert_eq!(version_key("6.1.0-rc3"), vec![6, 1, 0]);
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use crate::cgroup::Limits;
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::gigabytes;
+use crate::smbios::{MemoryDevice, Smbios};
+use crate::Source;
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryInfo {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    pub huge_pages: HugePages,
+    pub zram: Vec<ZramInfo>,
+    /// Memory limit of zfetch's cgroup, when below `total`
+    pub limit: Option<u64>,
+    /// Memory charged to that cgroup
+    pub limit_used: Option<u64>,
+    /// Installed modules from the SMBIOS table, only readable by root
+    pub modules: Vec<MemoryDevice>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HugePages {
+    pub total: u64,
+    pub free: u64,
+    /// Size of a single huge page in bytes
+    pub page_size: u64,
+}
+
+/// A compressed RAM block device, usually backing swap.
+#[derive(Debug, Clone)]
+pub struct ZramInfo {
+    pub name: String,
+    pub disk_size: u64,
+    /// Uncompressed size of the data stored
+    pub original: u64,
+    pub compressed: u64,
+    /// Memory actually used, including allocator overhead
+    pub memory_used: u64,
+}
+
+impl ZramInfo {
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed == 0 {
+            return 0.0;
+        }
+        self.original as f64 / self.compressed as f64
+    }
+
+    fn format_zram(&self) -> String {
+        format!(
+            "{}: {:.1} GB -> {:.1} GB ({:.1}x)",
+            self.name,
+            gigabytes(self.original),
+            gigabytes(self.memory_used),
+            self.compression_ratio()
+        )
+    }
+}
+
+impl MemoryInfo {
+    pub fn format_memory(&self) -> String {
+        if let Some(limit) = self.limit {
+            // In a container the host's totals say little about what the
+            // processes in it can use
+            let limit_gb = gigabytes(limit);
+            let used_gb = gigabytes(self.limit_used.unwrap_or(0));
+            let percentage = (used_gb / limit_gb * 100.0) as u8;
+            return format!(
+                "{:.1} GB / {:.1} GB ({}%, limit) — host {:.1} GB",
+                used_gb, limit_gb, percentage, gigabytes(self.total)
+            );
+        }
+
+        let total_gb = gigabytes(self.total);
+        let used_gb = gigabytes(self.used);
+        let percentage = (used_gb / total_gb * 100.0) as u8;
+
+        format!("{:.1} GB / {:.1} GB ({}%)", used_gb, total_gb, percentage)
+    }
+
+    pub fn format_swap(&self) -> Option<String> {
+        if self.swap_total == 0 {
+            return None;
+        }
+        let total_gb = gigabytes(self.swap_total);
+        let used_gb = gigabytes(self.swap_used);
+        let percentage = (used_gb / total_gb * 100.0) as u8;
+
+        Some(format!("{:.1} GB / {:.1} GB ({}%)", used_gb, total_gb, percentage))
+    }
+
+    /// Identical modules grouped, e.g. "2× 16 GB DDR5-5600".
+    pub fn format_modules(&self) -> Option<String> {
+        let mut groups: Vec<(&MemoryDevice, usize)> = Vec::new();
+        for module in &self.modules {
+            match groups.iter_mut().find(|(first, _)| same_kind(first, module)) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((module, 1)),
+            }
+        }
+        if groups.is_empty() {
+            return None;
+        }
+
+        let formatted: Vec<String> = groups.iter()
+            .map(|(module, count)| {
+                let mut group = format!("{}× {:.0} GB", count, gigabytes(module.size));
+                // Modules run below their rating without XMP/EXPO
+                let speed = module.configured_speed.or(module.speed);
+                match (module.memory_type, speed) {
+                    (Some(memory_type), Some(speed)) => group.push_str(&format!(" {}-{}", memory_type, speed)),
+                    (Some(memory_type), None) => group.push_str(&format!(" {}", memory_type)),
+                    (None, Some(speed)) => group.push_str(&format!(" @ {} MT/s", speed)),
+                    (None, None) => {}
+                }
+                group
+            })
+            .collect();
+        Some(formatted.join(", "))
+    }
+}
+
+fn same_kind(a: &MemoryDevice, b: &MemoryDevice) -> bool {
+    a.size == b.size
+        && a.memory_type == b.memory_type
+        && a.configured_speed.or(a.speed) == b.configured_speed.or(b.speed)
+}
+
+impl ModuleValue for MemoryInfo {
+    fn summary(&self) -> String {
+        self.format_memory()
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let mut rows = vec![(label.to_string(), self.format_memory())];
+        if let Some(swap) = self.format_swap() {
+            rows.push(("Swap".to_string(), swap));
+        }
+        if let Some(modules) = self.format_modules() {
+            rows.push((format!("{} Modules", label), modules));
+        }
+        for zram in &self.zram {
+            rows.push(("Zram".to_string(), zram.format_zram()));
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "total": self.total,
+            "used": self.used,
+            "available": self.available,
+            "buffers": self.buffers,
+            "cached": self.cached,
+            "shared": self.shared,
+            "swap": {
+                "total": self.swap_total,
+                "used": self.swap_used,
+                "formatted": self.format_swap()
+            },
+            "huge_pages": {
+                "total": self.huge_pages.total,
+                "free": self.huge_pages.free,
+                "page_size": self.huge_pages.page_size
+            },
+            "zram": self.zram.iter().map(|zram| serde_json::json!({
+                "name": zram.name,
+                "disk_size": zram.disk_size,
+                "original": zram.original,
+                "compressed": zram.compressed,
+                "memory_used": zram.memory_used,
+                "compression_ratio": zram.compression_ratio()
+            })).collect::<Vec<_>>(),
+            "limit": self.limit,
+            "limit_used": self.limit_used,
+            "modules": self.modules.iter().map(|module| serde_json::json!({
+                "locator": module.locator,
+                "bank": module.bank,
+                "size": module.size,
+                "type": module.memory_type,
+                "speed": module.speed,
+                "configured_speed": module.configured_speed,
+                "manufacturer": module.manufacturer,
+                "part_number": module.part_number
+            })).collect::<Vec<_>>(),
+            "formatted": self.format_memory()
+        })
+    }
+}
+
+pub struct MemoryModule;
+
+impl Module for MemoryModule {
+    type Value = MemoryInfo;
+
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn label(&self) -> &'static str {
+        "Memory"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<MemoryInfo> {
+        let meminfo = parse_meminfo(&ctx.source.read_to_string("/proc/meminfo").ok()?);
+        let field = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+
+        let total = field("MemTotal");
+        // Kernels before 3.14 have no MemAvailable
+        let available = meminfo.get("MemAvailable")
+            .copied()
+            .unwrap_or_else(|| field("MemFree") + field("Buffers") + field("Cached"));
+        let swap_total = field("SwapTotal");
+        let limits = Limits::read(&ctx.source);
+        let limited = limits.memory_max.filter(|&max| max > 0 && max < total);
+
+        Some(MemoryInfo {
+            total,
+            used: total.saturating_sub(available),
+            available,
+            buffers: field("Buffers"),
+            cached: field("Cached"),
+            shared: field("Shmem"),
+            swap_total,
+            swap_used: swap_total.saturating_sub(field("SwapFree")),
+            huge_pages: HugePages {
+                total: field("HugePages_Total"),
+                free: field("HugePages_Free"),
+                page_size: field("Hugepagesize"),
+            },
+            zram: zram_devices(&ctx.source),
+            limit: limited,
+            limit_used: limited.and(limits.memory_current),
+            modules: Smbios::read(&ctx.source).map(|table| table.memory_devices()).unwrap_or_default(),
+        })
+    }
+}
+
+/// Values of /proc/meminfo in bytes; HugePages_* are page counts.
+fn parse_meminfo(contents: &str) -> HashMap<String, u64> {
+    contents.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut parts = value.split_whitespace();
+            let number: u64 = parts.next()?.parse().ok()?;
+            let bytes = match parts.next() {
+                Some("kB") => number * 1024,
+                _ => number,
+            };
+            Some((key.to_string(), bytes))
+        })
+        .collect()
+}
+
+fn zram_devices(source: &Source) -> Vec<ZramInfo> {
+    let Ok(devices) = source.read_dir("/sys/block") else {
+        return Vec::new();
+    };
+
+    devices.iter()
+        .filter_map(|device| {
+            let name = device.file_name()?.to_str()?;
+            if !name.starts_with("zram") {
+                return None;
+            }
+
+            // orig_data_size compr_data_size mem_used_total mem_limit ...
+            let mm_stat = source.read_to_string(device.join("mm_stat")).ok()?;
+            let stats: Vec<u64> = mm_stat.split_whitespace()
+                .filter_map(|value| value.parse().ok())
+                .collect();
+            let disk_size = source.read_trimmed(device.join("disksize"))
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(0);
+            // Unconfigured devices have a zero disk size
+            if disk_size == 0 || stats.len() < 3 {
+                return None;
+            }
+
+            Some(ZramInfo {
+                name: name.to_string(),
+                disk_size,
+                original: stats[0],
+                compressed: stats[1],
+                memory_used: stats[2],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    const MEMINFO: &str = "\
+MemTotal:       16384000 kB
+MemFree:         2048000 kB
+MemAvailable:    8192000 kB
+Buffers:          512000 kB
+Cached:          4096000 kB
+Shmem:            256000 kB
+SwapTotal:       4096000 kB
+SwapFree:        3072000 kB
+HugePages_Total:       8
+HugePages_Free:        2
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn parses_meminfo_into_bytes() {
+        let meminfo = parse_meminfo(MEMINFO);
+        assert_eq!(meminfo["MemTotal"], 16_384_000 * 1024);
+        // Page counts carry no unit
+        assert_eq!(meminfo["HugePages_Total"], 8);
+    }
+
+    #[test]
+    fn collects_memory_swap_and_zram() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/meminfo", MEMINFO)
+            .file("/sys/block/zram0/disksize", "4194304000\n")
+            .file("/sys/block/zram0/mm_stat", "1000000000 250000000 260000000 0 260000000 0 0 0 0\n")
+            .file("/sys/block/zram1/disksize", "0\n")
+            .file("/sys/block/zram1/mm_stat", "0 0 0 0 0 0 0 0 0\n")
+            .dir("/sys/block/sda");
+
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        let memory = MemoryModule.collect(&ctx).unwrap();
+        assert_eq!(memory.used, 8_192_000 * 1024);
+        assert_eq!(memory.swap_used, 1_024_000 * 1024);
+        assert_eq!(memory.huge_pages.page_size, 2048 * 1024);
+        assert_eq!(memory.limit, None);
+        assert!(memory.modules.is_empty());
+
+        assert_eq!(memory.zram.len(), 1);
+        assert_eq!(memory.zram[0].name, "zram0");
+        assert_eq!(memory.zram[0].compression_ratio(), 4.0);
+    }
+
+    #[test]
+    fn falls_back_without_mem_available() {
+        let fixture = Fixture::new();
+        fixture.file("/proc/meminfo", "MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 250 kB\n");
+
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        assert_eq!(MemoryModule.collect(&ctx).unwrap().available, 400 * 1024);
+    }
+}
@@ -0,0 +1,49 @@
+//! Built-in modules. Each file holds one module and, when it needs more
+//! than a string, the value type it produces.
+
+mod battery;
+mod cpu;
+mod desktop;
+mod disk;
+mod displays;
+mod firmware;
+mod gpu;
+mod host;
+mod hostname;
+mod kernel;
+mod memory;
+mod network;
+mod os;
+mod packages;
+mod sensors;
+mod shell;
+mod terminal;
+mod uptime;
+mod user;
+mod virtualization;
+
+pub use battery::{BatteryInfo, BatteryModule, PowerInfo};
+pub use cpu::{CacheInfo, CpuInfo, CpuModule};
+pub use desktop::{DesktopInfo, DesktopModule};
+pub use disk::{DiskInfo, DiskModule};
+pub use displays::{DisplayInfo, DisplaysModule};
+pub use firmware::{FirmwareInfo, FirmwareModule};
+pub use gpu::{GpuInfo, GpuModule};
+pub use host::{HostInfo, HostModule};
+pub use hostname::HostnameModule;
+pub use kernel::KernelModule;
+pub use memory::{HugePages, MemoryInfo, MemoryModule, ZramInfo};
+pub use network::{InterfaceInfo, InterfaceKind, NetworkModule};
+pub use os::{OsInfo, OsModule};
+pub use packages::{PackageCount, PackagesModule};
+pub use sensors::{Sensor, SensorKind, SensorReadings, SensorsModule};
+pub use shell::{ShellInfo, ShellModule};
+pub use terminal::{TerminalInfo, TerminalModule};
+pub use uptime::{UptimeInfo, UptimeModule};
+pub use user::UserModule;
+pub use virtualization::{VirtInfo, VirtualizationModule};
+
+/// Bytes to GB the way zfetch prints them (binary units).
+pub(crate) fn gigabytes(bytes: u64) -> f64 {
+    bytes as f64 / 1_073_741_824.0
+}
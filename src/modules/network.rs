@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::ptr;
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceKind {
+    Ethernet,
+    Wifi,
+    Bridge,
+    Tun,
+    WireGuard,
+    Docker,
+    Virtual,
+}
+
+impl InterfaceKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ethernet => "ethernet",
+            Self::Wifi => "wifi",
+            Self::Bridge => "bridge",
+            Self::Tun => "tun",
+            Self::WireGuard => "wireguard",
+            Self::Docker => "docker",
+            Self::Virtual => "virtual",
+        }
+    }
+
+    /// Interfaces not backed by a network card.
+    pub fn is_virtual(&self) -> bool {
+        !matches!(self, Self::Ethernet | Self::Wifi)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceInfo {
+    pub name: String,
+    pub kind: InterfaceKind,
+    /// up, down, dormant, unknown... as reported by the kernel
+    pub operstate: String,
+    pub mac: Option<String>,
+    /// Link speed in Mb/s, unknown for most wireless and virtual links
+    pub speed: Option<u32>,
+    /// Addresses with their prefix length
+    pub addresses: Vec<(IpAddr, u8)>,
+}
+
+impl InterfaceInfo {
+    fn format_addresses(&self) -> Vec<String> {
+        self.addresses.iter()
+            .map(|(address, prefix)| format!("{}/{}", address, prefix))
+            .collect()
+    }
+
+    fn format_interface(&self) -> String {
+        let mut details = vec![self.kind.name().to_string()];
+        if let Some(speed) = self.speed {
+            details.push(format!("{} Mb/s", speed));
+        }
+        if self.operstate != "up" {
+            details.push(self.operstate.clone());
+        }
+
+        let addresses = self.format_addresses();
+        if addresses.is_empty() {
+            format!("[{}]", details.join(", "))
+        } else {
+            format!("{} [{}]", addresses.join(", "), details.join(", "))
+        }
+    }
+}
+
+impl ModuleValue for Vec<InterfaceInfo> {
+    fn summary(&self) -> String {
+        self.iter()
+            .map(|interface| format!("{}: {}", interface.name, interface.format_interface()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        self.iter()
+            .map(|interface| (format!("{} ({})", label, interface.name), interface.format_interface()))
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter()
+            .map(|interface| serde_json::json!({
+                "name": interface.name,
+                "kind": interface.kind.name(),
+                "operstate": interface.operstate,
+                "mac": interface.mac,
+                "speed": interface.speed,
+                "addresses": interface.format_addresses(),
+                "formatted": interface.format_interface()
+            }))
+            .collect()
+    }
+}
+
+pub struct NetworkModule {
+    /// Leave out bridges, tunnels, docker and other virtual interfaces
+    pub hide_virtual: bool,
+}
+
+impl Module for NetworkModule {
+    type Value = Vec<InterfaceInfo>;
+
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn label(&self) -> &'static str {
+        "Network"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<Vec<InterfaceInfo>> {
+        let source = &ctx.source;
+        let mut addresses = interface_addresses();
+        let mut interfaces = Vec::new();
+
+        for path in source.read_dir("/sys/class/net").ok()? {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let read = |file: &str| source.read_trimmed(path.join(file));
+
+            // ARPHRD_LOOPBACK
+            if read("type").as_deref() == Some("772") {
+                continue;
+            }
+
+            let operstate = read("operstate").unwrap_or_else(|| "unknown".to_string());
+            let interface_addresses = addresses.remove(name).unwrap_or_default();
+            // Tunnels report "unknown" even when in use
+            let active = operstate == "up" || (operstate == "unknown" && !interface_addresses.is_empty());
+            if !active {
+                continue;
+            }
+
+            let kind = interface_kind(source, &path, name);
+            if self.hide_virtual && kind.is_virtual() {
+                continue;
+            }
+
+            interfaces.push(InterfaceInfo {
+                name: name.to_string(),
+                kind,
+                operstate,
+                mac: read("address")
+                    .filter(|mac| !mac.is_empty() && mac != "00:00:00:00:00:00"),
+                // Reading speed fails with EINVAL on links that have none
+                speed: read("speed")
+                    .and_then(|speed| speed.parse::<i64>().ok())
+                    .filter(|&speed| speed > 0)
+                    .map(|speed| speed as u32),
+                addresses: interface_addresses,
+            });
+        }
+
+        (!interfaces.is_empty()).then_some(interfaces)
+    }
+}
+
+fn interface_kind(source: &Source, path: &Path, name: &str) -> InterfaceKind {
+    let uevent = source.read_to_string(path.join("uevent")).unwrap_or_default();
+    let devtype = uevent.lines()
+        .find_map(|line| line.strip_prefix("DEVTYPE="))
+        .unwrap_or("");
+
+    if devtype == "wlan" || source.exists(path.join("wireless")) || source.exists(path.join("phy80211")) {
+        InterfaceKind::Wifi
+    } else if devtype == "wireguard" {
+        InterfaceKind::WireGuard
+    } else if source.exists(path.join("tun_flags")) {
+        InterfaceKind::Tun
+    } else if name.starts_with("docker") || name.starts_with("br-") {
+        // Docker names its default bridge docker0 and user networks br-<id>
+        InterfaceKind::Docker
+    } else if devtype == "bridge" || source.exists(path.join("bridge")) {
+        InterfaceKind::Bridge
+    } else if !source.exists(path.join("device")) {
+        // Only interfaces backed by hardware have a device link
+        InterfaceKind::Virtual
+    } else {
+        InterfaceKind::Ethernet
+    }
+}
+
+/// IPv4 and IPv6 addresses of every interface, from getifaddrs(3).
+fn interface_addresses() -> HashMap<String, Vec<(IpAddr, u8)>> {
+    let mut addresses: HashMap<String, Vec<(IpAddr, u8)>> = HashMap::new();
+    let mut ifaddrs: *mut libc::ifaddrs = ptr::null_mut();
+
+    // SAFETY: getifaddrs fills in a linked list that stays valid until
+    // freeifaddrs; every pointer is checked before it is dereferenced and the
+    // sockaddr casts follow the address family
+    unsafe {
+        if libc::getifaddrs(&mut ifaddrs) != 0 {
+            return addresses;
+        }
+
+        let mut current = ifaddrs;
+        while !current.is_null() {
+            let ifa = &*current;
+            current = ifa.ifa_next;
+            if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+                continue;
+            }
+
+            let (address, prefix) = match i32::from((*ifa.ifa_addr).sa_family) {
+                libc::AF_INET => {
+                    let address = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                    let prefix = if ifa.ifa_netmask.is_null() {
+                        32
+                    } else {
+                        let netmask = &*(ifa.ifa_netmask as *const libc::sockaddr_in);
+                        netmask.sin_addr.s_addr.count_ones() as u8
+                    };
+                    (IpAddr::V4(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr))), prefix)
+                }
+                libc::AF_INET6 => {
+                    let address = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                    let prefix = if ifa.ifa_netmask.is_null() {
+                        128
+                    } else {
+                        let netmask = &*(ifa.ifa_netmask as *const libc::sockaddr_in6);
+                        netmask.sin6_addr.s6_addr.iter().map(|byte| byte.count_ones()).sum::<u32>() as u8
+                    };
+                    (IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr)), prefix)
+                }
+                _ => continue,
+            };
+
+            let name = CStr::from_ptr(ifa.ifa_name).to_string_lossy().into_owned();
+            addresses.entry(name).or_default().push((address, prefix));
+        }
+
+        libc::freeifaddrs(ifaddrs);
+    }
+
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    // Names no real host uses, so its addresses do not leak in
+    fn interface(fixture: &Fixture, name: &str, files: &[(&str, &str)]) {
+        fixture
+            .file(&format!("/sys/class/net/zf{}/operstate", name), "up\n")
+            .file(&format!("/sys/class/net/zf{}/type", name), "1\n");
+        for (file, contents) in files {
+            fixture.file(&format!("/sys/class/net/zf{}/{}", name, file), contents);
+        }
+    }
+
+    fn network(fixture: &Fixture) {
+        interface(fixture, "eth", &[("device/vendor", "0x8086\n"), ("speed", "1000\n")]);
+        interface(fixture, "wlan", &[("device/vendor", "0x8086\n"), ("uevent", "DEVTYPE=wlan\n"), ("speed", "-1\n")]);
+        interface(fixture, "br", &[("uevent", "DEVTYPE=bridge\nINTERFACE=zfbr\n"), ("bridge/stp_state", "0\n")]);
+        interface(fixture, "veth", &[]);
+        interface(fixture, "wg", &[("uevent", "DEVTYPE=wireguard\n")]);
+        interface(fixture, "lo", &[("type", "772\n")]);
+        interface(fixture, "down", &[("operstate", "down\n"), ("device/vendor", "0x10ec\n")]);
+    }
+
+    fn kinds(interfaces: &[InterfaceInfo]) -> Vec<(&str, &str)> {
+        interfaces.iter()
+            .map(|interface| (interface.name.as_str(), interface.kind.name()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_interfaces() {
+        let fixture = Fixture::new();
+        network(&fixture);
+        let source = fixture.source();
+        let kind = |name: &str| interface_kind(&source, Path::new(&format!("/sys/class/net/{}", name)), name);
+
+        assert_eq!(kind("zfeth"), InterfaceKind::Ethernet);
+        assert_eq!(kind("zfwlan"), InterfaceKind::Wifi);
+        assert_eq!(kind("zfbr"), InterfaceKind::Bridge);
+        assert_eq!(kind("zfveth"), InterfaceKind::Virtual);
+        assert_eq!(kind("zfwg"), InterfaceKind::WireGuard);
+        // Named by Docker, whatever sysfs says
+        assert_eq!(kind("docker0"), InterfaceKind::Docker);
+    }
+
+    #[test]
+    fn skips_loopback_and_interfaces_that_are_down() {
+        let fixture = Fixture::new();
+        network(&fixture);
+
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        let interfaces = NetworkModule { hide_virtual: false }.collect(&ctx).unwrap();
+        assert_eq!(kinds(&interfaces), vec![
+            ("zfbr", "bridge"),
+            ("zfeth", "ethernet"),
+            ("zfveth", "virtual"),
+            ("zfwg", "wireguard"),
+            ("zfwlan", "wifi"),
+        ]);
+        assert_eq!(interfaces[1].speed, Some(1000));
+        assert_eq!(interfaces[4].speed, None);
+    }
+
+    #[test]
+    fn net_filter_keeps_only_network_cards() {
+        let fixture = Fixture::new();
+        network(&fixture);
+
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        let interfaces = NetworkModule { hide_virtual: true }.collect(&ctx).unwrap();
+        assert_eq!(kinds(&interfaces), vec![("zfeth", "ethernet"), ("zfwlan", "wifi")]);
+    }
+}
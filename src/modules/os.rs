@@ -0,0 +1,66 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+#[derive(Debug, Clone)]
+pub struct OsInfo {
+    /// PRETTY_NAME from os-release
+    pub name: String,
+    /// ID from os-release, used for logo selection
+    pub id: String,
+}
+
+impl OsInfo {
+    pub fn read(source: &Source) -> Self {
+        let os_release = read_os_release(source);
+        let os_release = os_release.as_deref().unwrap_or("");
+
+        Self {
+            name: os_release_value(os_release, "PRETTY_NAME")
+                .unwrap_or_else(|| "Unknown Linux".to_string()),
+            id: os_release_value(os_release, "ID")
+                .unwrap_or_else(|| "linux".to_string()),
+        }
+    }
+}
+
+impl ModuleValue for OsInfo {
+    fn summary(&self) -> String {
+        self.name.clone()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.name)
+    }
+}
+
+pub struct OsModule;
+
+impl Module for OsModule {
+    type Value = OsInfo;
+
+    fn name(&self) -> &'static str {
+        "os_name"
+    }
+
+    fn label(&self) -> &'static str {
+        "OS"
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<OsInfo> {
+        Some(OsInfo::read(&ctx.source))
+    }
+}
+
+pub(crate) fn read_os_release(source: &Source) -> Option<String> {
+    // Per os-release(5), /usr/lib/os-release is the fallback
+    source.read_to_string("/etc/os-release")
+        .or_else(|_| source.read_to_string("/usr/lib/os-release"))
+        .ok()
+}
+
+pub(crate) fn os_release_value(contents: &str, key: &str) -> Option<String> {
+    contents.lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        // Remove quotes if present
+        .map(|value| value.trim_matches('"').to_string())
+}
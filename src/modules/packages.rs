@@ -0,0 +1,394 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::os::{os_release_value, read_os_release};
+use crate::sqlite::Database;
+use crate::Source;
+
+type Counter = fn(&Source) -> Option<usize>;
+
+// Every backend reads the package database directly; none of them forks
+const MANAGERS: &[(&str, Counter)] = &[
+    ("dpkg", count_dpkg),
+    ("pacman", count_pacman),
+    ("rpm", count_rpm),
+    ("apk", count_apk),
+    ("xbps", count_xbps),
+    ("portage", count_portage),
+    ("nix-system", count_nix_system),
+    ("nix-user", count_nix_user),
+    ("nix-default", count_nix_default),
+    ("flatpak", count_flatpak),
+    ("snap", count_snap),
+];
+
+// os-release ID or ID_LIKE -> the distribution's own package manager
+const NATIVE_MANAGERS: &[(&str, &str)] = &[
+    ("debian", "dpkg"),
+    ("ubuntu", "dpkg"),
+    ("arch", "pacman"),
+    ("fedora", "rpm"),
+    ("rhel", "rpm"),
+    ("centos", "rpm"),
+    ("suse", "rpm"),
+    ("opensuse", "rpm"),
+    ("mageia", "rpm"),
+    ("alpine", "apk"),
+    ("void", "xbps"),
+    ("gentoo", "portage"),
+    ("nixos", "nix-system"),
+];
+
+const RPM_DATABASES: &[&str] = &[
+    "/var/lib/rpm/rpmdb.sqlite",
+    "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+];
+
+#[derive(Debug, Clone)]
+pub struct PackageCount {
+    pub manager: &'static str,
+    pub count: usize,
+}
+
+impl ModuleValue for Vec<PackageCount> {
+    fn summary(&self) -> String {
+        self.iter()
+            .map(|packages| format!("{} ({})", packages.count, packages.manager))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter()
+            .map(|packages| serde_json::json!({
+                "manager": packages.manager,
+                "count": packages.count
+            }))
+            .collect()
+    }
+}
+
+pub struct PackagesModule;
+
+impl Module for PackagesModule {
+    type Value = Vec<PackageCount>;
+
+    fn name(&self) -> &'static str {
+        "packages"
+    }
+
+    fn label(&self) -> &'static str {
+        "Packages"
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<Vec<PackageCount>> {
+        let source = &ctx.source;
+        let native = native_managers(source);
+
+        // The distribution's own manager first, then whatever else is found
+        let mut managers = MANAGERS.to_vec();
+        managers.sort_by_key(|(manager, _)| {
+            native.iter().position(|native| native == manager).unwrap_or(native.len())
+        });
+
+        let counts: Vec<PackageCount> = managers.into_iter()
+            .filter_map(|(manager, count)| {
+                let count = count(source).filter(|&count| count > 0)?;
+                Some(PackageCount { manager, count })
+            })
+            .collect();
+
+        (!counts.is_empty()).then_some(counts)
+    }
+}
+
+/// Package managers of the distribution and the ones it derives from.
+fn native_managers(source: &Source) -> Vec<&'static str> {
+    let os_release = read_os_release(source).unwrap_or_default();
+    let ids = os_release_value(&os_release, "ID").into_iter()
+        .chain(os_release_value(&os_release, "ID_LIKE"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut managers = Vec::new();
+    for id in ids.split_whitespace() {
+        let found = NATIVE_MANAGERS.iter()
+            .find(|(native_id, _)| id == *native_id || id.starts_with(&format!("{}-", native_id)));
+        if let Some(&(_, manager)) = found {
+            if !managers.contains(&manager) {
+                managers.push(manager);
+            }
+        }
+    }
+    managers
+}
+
+fn count_dpkg(source: &Source) -> Option<usize> {
+    let status = source.read_to_string("/var/lib/dpkg/status").ok()?;
+    // Removed packages keep a paragraph with their conffiles
+    Some(
+        status.lines()
+            .filter(|line| line.starts_with("Status: ") && line.ends_with(" installed"))
+            .count()
+    )
+}
+
+fn count_pacman(source: &Source) -> Option<usize> {
+    let packages = source.read_dir("/var/lib/pacman/local").ok()?;
+    // Skips the ALPM_DB_VERSION file
+    Some(packages.iter().filter(|package| source.exists(package.join("desc"))).count())
+}
+
+fn count_rpm(source: &Source) -> Option<usize> {
+    RPM_DATABASES.iter().find_map(|path| {
+        let database = Database::open(source.open(path).ok()?)?;
+        Some(database.count_rows("Packages")? as usize)
+    })
+}
+
+fn count_apk(source: &Source) -> Option<usize> {
+    let installed = source.read_to_string("/lib/apk/db/installed").ok()?;
+    Some(installed.lines().filter(|line| line.starts_with("P:")).count())
+}
+
+fn count_xbps(source: &Source) -> Option<usize> {
+    // pkgdb-0.38.plist, the version changes with the format
+    let pkgdb = source.read_dir("/var/db/xbps").ok()?
+        .into_iter()
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("pkgdb-") && name.ends_with(".plist"))
+        })?;
+    let contents = source.read_to_string(pkgdb).ok()?;
+    Some(contents.matches("<string>installed</string>").count())
+}
+
+fn count_portage(source: &Source) -> Option<usize> {
+    let categories = source.read_dir("/var/db/pkg").ok()?;
+    Some(
+        categories.iter()
+            .filter_map(|category| source.read_dir(category).ok())
+            .flatten()
+            // Skips the -MERGING- directories of an interrupted merge
+            .filter(|package| source.exists(package.join("CONTENTS")))
+            .count()
+    )
+}
+
+fn count_flatpak(source: &Source) -> Option<usize> {
+    let mut installations = vec![PathBuf::from("/var/lib/flatpak")];
+    if let Some(home) = home_dir() {
+        installations.push(home.join(".local/share/flatpak"));
+    }
+
+    // Every deployed ref is <kind>/<id>/<arch>/<branch>
+    let count = installations.iter()
+        .flat_map(|installation| ["app", "runtime"].map(|kind| installation.join(kind)))
+        .filter_map(|dir| source.read_dir(dir).ok())
+        .flatten()
+        .filter_map(|id| source.read_dir(id).ok())
+        .flatten()
+        .filter_map(|arch| source.read_dir(arch).ok())
+        .flatten()
+        .filter(|branch| source.exists(branch.join("active")))
+        .count();
+    Some(count)
+}
+
+fn count_snap(source: &Source) -> Option<usize> {
+    let snaps = source.read_dir("/snap").ok()?;
+    // Skips /snap/bin and the README
+    Some(snaps.iter().filter(|snap| source.exists(snap.join("current"))).count())
+}
+
+fn count_nix_system(source: &Source) -> Option<usize> {
+    count_nix_profile(source, PathBuf::from("/run/current-system/sw"))
+}
+
+fn count_nix_user(source: &Source) -> Option<usize> {
+    count_nix_profile(source, home_dir()?.join(".nix-profile"))
+}
+
+fn count_nix_default(source: &Source) -> Option<usize> {
+    count_nix_profile(source, PathBuf::from("/nix/var/nix/profiles/default"))
+}
+
+/// Packages in a Nix profile. Profiles managed by `nix profile` or
+/// `nix-env` list them in a manifest; NixOS's system profile is a plain
+/// buildEnv, where the store paths its binaries link to are counted instead.
+fn count_nix_profile(source: &Source, profile: PathBuf) -> Option<usize> {
+    if let Ok(manifest) = source.read_to_string(profile.join("manifest.json")) {
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+        // An array in version 1, an object keyed by name since version 3
+        return match &manifest["elements"] {
+            serde_json::Value::Array(elements) => Some(elements.len()),
+            serde_json::Value::Object(elements) => Some(elements.len()),
+            _ => None,
+        };
+    }
+
+    if let Ok(manifest) = source.read_to_string(profile.join("manifest.nix")) {
+        return Some(manifest.matches("type = \"derivation\"").count());
+    }
+
+    let binaries = source.read_dir(profile.join("bin")).ok()?;
+    let store_paths: BTreeSet<PathBuf> = binaries.iter()
+        .filter_map(|binary| source.read_link(binary).ok())
+        // /nix/store/<hash>-<name>/bin/<binary> -> /nix/store/<hash>-<name>
+        .filter(|target| target.starts_with("/nix/store"))
+        .map(|target| target.components().take(4).collect::<PathBuf>())
+        .filter(|path| path.components().count() == 4)
+        .collect();
+    Some(store_paths.len())
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from).filter(|home| home.is_absolute())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::time::Duration;
+
+    #[test]
+    fn counts_dpkg_packages_still_installed() {
+        let fixture = Fixture::new();
+        fixture.file("/var/lib/dpkg/status", "\
+Package: bash
+Status: install ok installed
+Version: 5.2.21-2
+
+Package: nano
+Status: deinstall ok config-files
+Version: 7.2-2
+
+Package: vim
+Status: hold ok installed
+
+Package: broken
+Status: install reinstreq half-installed
+");
+        assert_eq!(count_dpkg(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_pacman_packages_with_a_desc() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/var/lib/pacman/local/ALPM_DB_VERSION", "9\n")
+            .file("/var/lib/pacman/local/bash-5.2.026-2/desc", "%NAME%\nbash\n")
+            .file("/var/lib/pacman/local/linux-6.9.7.arch1-1/desc", "%NAME%\nlinux\n")
+            .dir("/var/lib/pacman/local/half-removed-1.0-1");
+        assert_eq!(count_pacman(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_apk_packages() {
+        let fixture = Fixture::new();
+        fixture.file("/lib/apk/db/installed", "\
+C:Q1abc=
+P:musl
+V:1.2.5-r0
+
+C:Q1def=
+P:busybox
+V:1.36.1-r29
+");
+        assert_eq!(count_apk(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_installed_xbps_packages() {
+        let fixture = Fixture::new();
+        fixture.file("/var/db/xbps/pkgdb-0.38.plist", "\
+<dict>
+	<key>base-files</key>
+	<dict>
+		<key>state</key>
+		<string>installed</string>
+	</dict>
+	<key>old-package</key>
+	<dict>
+		<key>state</key>
+		<string>half-removed</string>
+	</dict>
+	<key>xbps</key>
+	<dict>
+		<key>state</key>
+		<string>installed</string>
+	</dict>
+</dict>
+");
+        assert_eq!(count_xbps(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_merged_portage_packages() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/var/db/pkg/app-shells/bash-5.2_p26/CONTENTS", "")
+            .file("/var/db/pkg/sys-apps/portage-3.0.63/CONTENTS", "")
+            .dir("/var/db/pkg/sys-apps/-MERGING-coreutils-9.5");
+        assert_eq!(count_portage(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_deployed_flatpak_refs() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/var/lib/flatpak/app/org.mozilla.firefox/x86_64/stable/active")
+            .dir("/var/lib/flatpak/runtime/org.freedesktop.Platform/x86_64/23.08/active")
+            // Pulled but never deployed
+            .dir("/var/lib/flatpak/runtime/org.gnome.Platform/x86_64/46");
+        assert_eq!(count_flatpak(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_snaps_with_a_current_revision() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/snap/core22/1380")
+            .symlink("/snap/core22/current", "1380")
+            .dir("/snap/firefox/4336")
+            .symlink("/snap/firefox/current", "4336")
+            // Removed, with only a dangling link left
+            .symlink("/snap/gone/current", "12")
+            .dir("/snap/bin")
+            .file("/snap/README", "");
+        assert_eq!(count_snap(&fixture.source()), Some(2));
+    }
+
+    #[test]
+    fn counts_nix_profiles() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/nix/var/nix/profiles/default/manifest.json",
+                  r#"{"version": 3, "elements": {"hello": {}, "ripgrep": {}}}"#)
+            .symlink("/run/current-system/sw/bin/bash", "/nix/store/aaaa-bash-5.2p26/bin/bash")
+            .symlink("/run/current-system/sw/bin/sh", "/nix/store/aaaa-bash-5.2p26/bin/sh")
+            .symlink("/run/current-system/sw/bin/ls", "/nix/store/bbbb-coreutils-9.5/bin/ls");
+
+        let source = fixture.source();
+        assert_eq!(count_nix_default(&source), Some(2));
+        // bash and sh come from the same store path
+        assert_eq!(count_nix_system(&source), Some(2));
+    }
+
+    #[test]
+    fn lists_the_native_manager_first() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/etc/os-release", "ID=ubuntu\nID_LIKE=debian\n")
+            .file("/var/lib/dpkg/status", "Package: bash\nStatus: install ok installed\n")
+            .dir("/snap/core22/1380")
+            .symlink("/snap/core22/current", "1380")
+            .file("/var/lib/pacman/local/ALPM_DB_VERSION", "9\n");
+
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        let counts = PackagesModule.collect(&ctx).unwrap();
+        assert_eq!(counts.summary(), "1 (dpkg), 1 (snap)");
+    }
+}
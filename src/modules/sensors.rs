@@ -0,0 +1,357 @@
+use std::path::Path;
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+// hwmon chips and labels that report the CPU package as a whole
+const CPU_PACKAGE_SENSORS: &[(&str, Option<&str>)] = &[
+    ("coretemp", Some("Package id 0")),
+    ("k10temp", Some("Tctl")),
+    ("k10temp", Some("Tdie")),
+    ("zenpower", Some("Tdie")),
+    ("cpu_thermal", None),
+    ("cpu-thermal", None),
+];
+// Thermal zone types used when no hwmon chip matches, mostly on ARM
+const CPU_THERMAL_ZONES: &[&str] = &["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal", "cpu0-thermal"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+}
+
+impl SensorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Fan => "fan",
+            Self::Voltage => "voltage",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Temperature => "°C",
+            Self::Fan => "RPM",
+            Self::Voltage => "V",
+        }
+    }
+
+    /// sysfs attribute prefix and the divisor to get to `unit()`.
+    fn attribute(&self) -> (&'static str, f64) {
+        match self {
+            // millidegrees Celsius
+            Self::Temperature => ("temp", 1000.0),
+            Self::Fan => ("fan", 1.0),
+            // millivolts
+            Self::Voltage => ("in", 1000.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sensor {
+    /// hwmon chip name or thermal zone type, e.g. coretemp or acpitz
+    pub chip: String,
+    pub label: String,
+    pub kind: SensorKind,
+    pub value: f64,
+}
+
+impl Sensor {
+    pub fn format_value(&self) -> String {
+        format_reading(self.kind, self.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SensorReadings {
+    pub sensors: Vec<Sensor>,
+}
+
+impl ModuleValue for SensorReadings {
+    fn summary(&self) -> String {
+        self.sensors.iter()
+            .map(|sensor| format!("{} {}: {}", sensor.chip, sensor.label, sensor.format_value()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        self.sensors.iter()
+            .map(|sensor| (format!("{} ({} {})", label, sensor.chip, sensor.label), sensor.format_value()))
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.sensors.iter()
+            .map(|sensor| serde_json::json!({
+                "chip": sensor.chip,
+                "label": sensor.label,
+                "type": sensor.kind.name(),
+                "value": sensor.value,
+                "unit": sensor.kind.unit()
+            }))
+            .collect()
+    }
+}
+
+/// Every sensor, one row each. Only registered with --sensors; the CPU and
+/// GPU lines read their own temperature otherwise.
+pub struct SensorsModule;
+
+impl Module for SensorsModule {
+    type Value = SensorReadings;
+
+    fn name(&self) -> &'static str {
+        "sensors"
+    }
+
+    fn label(&self) -> &'static str {
+        "Sensor"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<SensorReadings> {
+        let source = &ctx.source;
+        let mut sensors = Vec::new();
+
+        for chip in source.read_dir("/sys/class/hwmon").unwrap_or_default() {
+            sensors.extend(hwmon_sensors(source, &chip));
+        }
+
+        for zone in source.read_dir("/sys/class/thermal").unwrap_or_default() {
+            let is_zone = zone.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("thermal_zone"));
+            // Zones registered with hwmon were already read above
+            if !is_zone || has_hwmon(source, &zone) {
+                continue;
+            }
+            let (Some(zone_type), Some(value)) = (
+                source.read_trimmed(zone.join("type")),
+                read_number(source, zone.join("temp")),
+            ) else {
+                continue;
+            };
+            sensors.push(Sensor {
+                label: zone.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+                chip: zone_type,
+                kind: SensorKind::Temperature,
+                value: value / 1000.0,
+            });
+        }
+
+        (!sensors.is_empty()).then_some(SensorReadings { sensors })
+    }
+}
+
+/// Temperatures, fans and voltages of one hwmon chip.
+fn hwmon_sensors(source: &Source, chip: &Path) -> Vec<Sensor> {
+    let name = source.read_trimmed(chip.join("name")).unwrap_or_else(|| "hwmon".to_string());
+    let Ok(attributes) = source.read_dir(chip) else {
+        return Vec::new();
+    };
+
+    let mut sensors = Vec::new();
+    for kind in [SensorKind::Temperature, SensorKind::Fan, SensorKind::Voltage] {
+        let (prefix, divisor) = kind.attribute();
+        for attribute in &attributes {
+            // temp1_input, fan2_input, in0_input
+            let Some(channel) = attribute.file_name()
+                .and_then(|file| file.to_str())
+                .and_then(|file| file.strip_prefix(prefix)?.strip_suffix("_input"))
+                .filter(|channel| channel.bytes().all(|byte| byte.is_ascii_digit()))
+            else {
+                continue;
+            };
+            let Some(value) = read_number(source, attribute) else {
+                continue;
+            };
+            sensors.push(Sensor {
+                chip: name.clone(),
+                label: source.read_trimmed(chip.join(format!("{}{}_label", prefix, channel)))
+                    .unwrap_or_else(|| format!("{}{}", prefix, channel)),
+                kind,
+                value: value / divisor,
+            });
+        }
+    }
+    sensors
+}
+
+/// Temperature of the CPU package in °C.
+pub(crate) fn cpu_temperature(source: &Source) -> Option<f64> {
+    let chips: Vec<Vec<Sensor>> = source.read_dir("/sys/class/hwmon")
+        .unwrap_or_default()
+        .iter()
+        .map(|chip| hwmon_sensors(source, chip))
+        .collect();
+
+    for (chip_name, label) in CPU_PACKAGE_SENSORS {
+        let found = chips.iter().flatten().find(|sensor| {
+            sensor.kind == SensorKind::Temperature
+                && sensor.chip == *chip_name
+                && label.is_none_or(|label| sensor.label == label)
+        });
+        if let Some(sensor) = found {
+            return Some(sensor.value);
+        }
+    }
+
+    source.read_dir("/sys/class/thermal")
+        .unwrap_or_default()
+        .iter()
+        .filter(|zone| {
+            source.read_trimmed(zone.join("type")).is_some_and(|zone_type| CPU_THERMAL_ZONES.contains(&zone_type.as_str()))
+        })
+        .find_map(|zone| read_number(source, zone.join("temp")))
+        .map(|value| value / 1000.0)
+}
+
+/// Temperature of a PCI device such as a GPU, from the hwmon chip its
+/// driver registers. amdgpu labels the one that matters "edge".
+pub(crate) fn pci_temperature(source: &Source, slot: &str) -> Option<f64> {
+    let sensors: Vec<Sensor> = source.read_dir(format!("/sys/bus/pci/devices/{}/hwmon", slot))
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|chip| hwmon_sensors(source, chip))
+        .filter(|sensor| sensor.kind == SensorKind::Temperature)
+        .collect();
+
+    sensors.iter()
+        .find(|sensor| sensor.label == "edge")
+        .or_else(|| sensors.first())
+        .map(|sensor| sensor.value)
+}
+
+pub(crate) fn format_reading(kind: SensorKind, value: f64) -> String {
+    match kind {
+        SensorKind::Temperature => format!("{:.1}{}", value, kind.unit()),
+        SensorKind::Fan => format!("{:.0} {}", value, kind.unit()),
+        SensorKind::Voltage => format!("{:.2} {}", value, kind.unit()),
+    }
+}
+
+fn has_hwmon(source: &Source, zone: &Path) -> bool {
+    source.read_dir(zone)
+        .unwrap_or_default()
+        .iter()
+        .any(|entry| entry.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("hwmon")))
+}
+
+fn read_number(source: &Source, path: impl AsRef<Path>) -> Option<f64> {
+    source.read_trimmed(path)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use crate::module::Context;
+    use std::time::Duration;
+
+    /// An Intel desktop: coretemp, a Super I/O chip with fans and voltages,
+    /// and an ACPI zone that hwmon already covers.
+    fn intel_desktop() -> Fixture {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/hwmon/hwmon0/name", "acpitz\n")
+            .file("/sys/class/hwmon/hwmon0/temp1_input", "27800\n")
+            .file("/sys/class/hwmon/hwmon1/name", "coretemp\n")
+            .file("/sys/class/hwmon/hwmon1/temp1_label", "Package id 0\n")
+            .file("/sys/class/hwmon/hwmon1/temp1_input", "45000\n")
+            .file("/sys/class/hwmon/hwmon1/temp1_max", "80000\n")
+            .file("/sys/class/hwmon/hwmon1/temp2_label", "Core 0\n")
+            .file("/sys/class/hwmon/hwmon1/temp2_input", "43500\n")
+            .file("/sys/class/hwmon/hwmon2/name", "nct6798\n")
+            .file("/sys/class/hwmon/hwmon2/fan2_input", "1181\n")
+            .file("/sys/class/hwmon/hwmon2/in0_label", "Vcore\n")
+            .file("/sys/class/hwmon/hwmon2/in0_input", "1024\n")
+            .file("/sys/class/hwmon/hwmon2/in1_input", "\n")
+            .file("/sys/class/thermal/thermal_zone0/type", "acpitz\n")
+            .file("/sys/class/thermal/thermal_zone0/temp", "27800\n")
+            .dir("/sys/class/thermal/thermal_zone0/hwmon0")
+            .file("/sys/class/thermal/thermal_zone1/type", "x86_pkg_temp\n")
+            .file("/sys/class/thermal/thermal_zone1/temp", "46000\n")
+            .file("/sys/class/thermal/cooling_device0/type", "Processor\n");
+        fixture
+    }
+
+    fn readings(sensors: &SensorReadings) -> Vec<(&str, &str, SensorKind, f64)> {
+        sensors.sensors.iter()
+            .map(|sensor| (sensor.chip.as_str(), sensor.label.as_str(), sensor.kind, sensor.value))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_labels_with_inputs() {
+        let fixture = intel_desktop();
+        let ctx = Context::new(fixture.source(), Duration::from_secs(1));
+        let sensors = SensorsModule.collect(&ctx).unwrap();
+
+        assert_eq!(readings(&sensors), vec![
+            ("acpitz", "temp1", SensorKind::Temperature, 27.8),
+            ("coretemp", "Package id 0", SensorKind::Temperature, 45.0),
+            ("coretemp", "Core 0", SensorKind::Temperature, 43.5),
+            ("nct6798", "fan2", SensorKind::Fan, 1181.0),
+            ("nct6798", "Vcore", SensorKind::Voltage, 1.024),
+            ("x86_pkg_temp", "thermal_zone1", SensorKind::Temperature, 46.0),
+        ]);
+        assert_eq!(sensors.rows("Sensor")[1], ("Sensor (coretemp Package id 0)".to_string(), "45.0°C".to_string()));
+        assert_eq!(sensors.sensors[3].format_value(), "1181 RPM");
+        assert_eq!(sensors.sensors[4].format_value(), "1.02 V");
+        assert_eq!(sensors.to_json()[4]["unit"], "V");
+    }
+
+    #[test]
+    fn picks_the_cpu_package_sensor() {
+        let fixture = intel_desktop();
+        assert_eq!(cpu_temperature(&fixture.source()), Some(45.0));
+
+        // k10temp's Tctl wins over the per-CCD readings
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/hwmon/hwmon0/name", "k10temp\n")
+            .file("/sys/class/hwmon/hwmon0/temp3_label", "Tccd1\n")
+            .file("/sys/class/hwmon/hwmon0/temp3_input", "38250\n")
+            .file("/sys/class/hwmon/hwmon0/temp1_label", "Tctl\n")
+            .file("/sys/class/hwmon/hwmon0/temp1_input", "41625\n");
+        assert_eq!(cpu_temperature(&fixture.source()), Some(41.625));
+    }
+
+    #[test]
+    fn falls_back_to_cpu_thermal_zones() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/class/thermal/thermal_zone0/type", "gpu-thermal\n")
+            .file("/sys/class/thermal/thermal_zone0/temp", "51000\n")
+            .file("/sys/class/thermal/thermal_zone1/type", "soc_thermal\n")
+            .file("/sys/class/thermal/thermal_zone1/temp", "48312\n");
+        assert_eq!(cpu_temperature(&fixture.source()), Some(48.312));
+
+        assert_eq!(cpu_temperature(&Fixture::new().source()), None);
+    }
+
+    #[test]
+    fn reads_the_gpu_edge_temperature() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/sys/bus/pci/devices/0000:03:00.0/hwmon/hwmon4/name", "amdgpu\n")
+            .file("/sys/bus/pci/devices/0000:03:00.0/hwmon/hwmon4/temp1_label", "edge\n")
+            .file("/sys/bus/pci/devices/0000:03:00.0/hwmon/hwmon4/temp1_input", "52000\n")
+            .file("/sys/bus/pci/devices/0000:03:00.0/hwmon/hwmon4/temp2_label", "junction\n")
+            .file("/sys/bus/pci/devices/0000:03:00.0/hwmon/hwmon4/temp2_input", "58000\n")
+            .file("/sys/bus/pci/devices/0000:01:00.0/hwmon/hwmon5/name", "nouveau\n")
+            .file("/sys/bus/pci/devices/0000:01:00.0/hwmon/hwmon5/temp1_input", "39000\n");
+
+        let source = fixture.source();
+        assert_eq!(pci_temperature(&source, "0000:03:00.0"), Some(52.0));
+        assert_eq!(pci_temperature(&source, "0000:01:00.0"), Some(39.0));
+        assert_eq!(pci_temperature(&source, "0000:00:02.0"), None);
+    }
+}
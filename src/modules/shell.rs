@@ -0,0 +1,203 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+pub(crate) const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "tcsh", "csh", "nu", "elvish",
+    "xonsh", "ion", "oksh", "yash",
+];
+// Processes that may sit between a shell and zfetch
+pub(crate) const WRAPPERS: &[&str] = &[
+    "sudo", "sudo-rs", "su", "doas", "login", "env", "script", "nohup", "time", "watch",
+    "strace", "cargo", "zfetch",
+];
+
+// Shells that export their version, which saves running the binary. Most
+// keep it as an unexported shell variable, so it is rarely there.
+const VERSION_VARIABLES: &[(&str, &str)] = &[
+    ("bash", "BASH_VERSION"),
+    ("zsh", "ZSH_VERSION"),
+    ("fish", "FISH_VERSION"),
+];
+// Shells that answer --version on stdout; dash and ksh have no such flag
+const VERSION_FLAG_SHELLS: &[&str] = &["bash", "zsh", "fish", "nu", "elvish", "xonsh", "tcsh", "yash"];
+
+#[derive(Debug, Clone)]
+pub struct ShellInfo {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl ShellInfo {
+    fn format_shell(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl ModuleValue for ShellInfo {
+    fn summary(&self) -> String {
+        self.format_shell()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "version": self.version,
+            "formatted": self.format_shell()
+        })
+    }
+}
+
+pub struct ShellModule;
+
+impl Module for ShellModule {
+    type Value = ShellInfo;
+
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn label(&self) -> &'static str {
+        "Shell"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<ShellInfo> {
+        let source = &ctx.source;
+
+        // $SHELL is the login shell, which is not necessarily the one
+        // zfetch was started from
+        let Some((pid, name)) = parent_shell(source) else {
+            let name = std::env::var("SHELL")
+                .unwrap_or_else(|_| "unknown".to_string())
+                .split('/')
+                .next_back()
+                .unwrap_or("unknown")
+                .to_string();
+            return Some(ShellInfo { name, version: None });
+        };
+
+        let version = shell_version(ctx, pid, &name);
+        Some(ShellInfo { name, version })
+    }
+}
+
+/// Pid and name of the shell zfetch runs in, skipping sudo and the like.
+fn parent_shell(source: &Source) -> Option<(u32, String)> {
+    let mut pid = parent_pid(source, "self")?;
+
+    for _ in 0..WRAPPERS.len() + 1 {
+        let comm = process_name(source, pid)?;
+        if SHELLS.contains(&comm.as_str()) {
+            return Some((pid, comm));
+        }
+        if !WRAPPERS.contains(&comm.as_str()) {
+            return None;
+        }
+        pid = parent_pid(source, &pid.to_string())?;
+    }
+
+    None
+}
+
+fn shell_version(ctx: &Context, pid: u32, name: &str) -> Option<String> {
+    let variable = VERSION_VARIABLES.iter().find(|(shell, _)| *shell == name);
+    if let Some(version) = variable.and_then(|(_, variable)| std::env::var(variable).ok()) {
+        return parse_version(&version);
+    }
+
+    if !VERSION_FLAG_SHELLS.contains(&name) {
+        return None;
+    }
+    // Ask the binary the process runs, not whichever one is first in $PATH
+    let exe = ctx.source.read_link(format!("/proc/{}/exe", pid)).ok()?;
+    let output = ctx.command_output(exe.to_str()?, &["--version"])?;
+    parse_version(output.lines().next()?)
+}
+
+/// First version-looking word: "GNU bash, version 5.2.21(1)-release" ->
+/// "5.2.21", "zsh 5.9 (x86_64-pc-linux-gnu)" -> "5.9", "xonsh/0.14.0" ->
+/// "0.14.0".
+fn parse_version(text: &str) -> Option<String> {
+    let word = text.split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+    let end = word.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.')).unwrap_or(word.len());
+    Some(word[..end].to_string())
+}
+
+pub(crate) fn process_name(source: &Source, pid: u32) -> Option<String> {
+    let comm = source.read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end_matches('\n').to_string())
+}
+
+/// Parent pid from /proc/<pid>/stat: "pid (comm) state ppid ...". comm may
+/// itself contain spaces and parentheses, so fields are counted from the
+/// last ')'.
+pub(crate) fn parent_pid(source: &Source, pid: &str) -> Option<u32> {
+    let stat = source.read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// /proc entries for `pid` named `comm`, started by `parent`.
+    fn process(fixture: &Fixture, pid: &str, comm: &str, parent: u32) {
+        fixture
+            .file(&format!("/proc/{}/comm", pid), format!("{}\n", comm))
+            .file(&format!("/proc/{}/stat", pid), format!("{} ({}) S {} 4242 4242 34816 0 4194304", pid, comm, parent));
+    }
+
+    #[test]
+    fn parses_version_banners() {
+        assert_eq!(parse_version("GNU bash, version 5.2.21(1)-release (x86_64-pc-linux-gnu)").as_deref(), Some("5.2.21"));
+        assert_eq!(parse_version("5.2.21(1)-release").as_deref(), Some("5.2.21"));
+        assert_eq!(parse_version("zsh 5.9 (x86_64-pc-linux-gnu)").as_deref(), Some("5.9"));
+        assert_eq!(parse_version("fish, version 3.7.1").as_deref(), Some("3.7.1"));
+        assert_eq!(parse_version("0.95.0").as_deref(), Some("0.95.0"));
+        assert_eq!(parse_version("tcsh 6.24.10 (Astron) 2023-04-14 (x86_64-unknown-linux)").as_deref(), Some("6.24.10"));
+        assert_eq!(parse_version("nushell"), None);
+    }
+
+    #[test]
+    fn reads_the_parent_pid_past_spaces_in_comm() {
+        let fixture = Fixture::new();
+        process(&fixture, "1300", "tmux: server", 1);
+        // comm may even hold ") S 99 "
+        process(&fixture, "1400", "evil) S 99 (x", 1300);
+
+        let source = fixture.source();
+        assert_eq!(parent_pid(&source, "1300"), Some(1));
+        assert_eq!(parent_pid(&source, "1400"), Some(1300));
+        assert_eq!(parent_pid(&source, "1500"), None);
+    }
+
+    #[test]
+    fn finds_the_shell_behind_wrappers() {
+        let fixture = Fixture::new();
+        process(&fixture, "self", "zfetch", 1200);
+        process(&fixture, "1200", "sudo", 1100);
+        process(&fixture, "1100", "zsh", 1000);
+        process(&fixture, "1000", "kitty", 1);
+
+        assert_eq!(parent_shell(&fixture.source()), Some((1100, "zsh".to_string())));
+    }
+
+    #[test]
+    fn other_parents_are_no_shell() {
+        let fixture = Fixture::new();
+        process(&fixture, "self", "zfetch", 1200);
+        process(&fixture, "1200", "python3", 1100);
+        process(&fixture, "1100", "bash", 1);
+
+        assert_eq!(parent_shell(&fixture.source()), None);
+    }
+}
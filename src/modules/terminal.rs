@@ -0,0 +1,263 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::modules::shell::{parent_pid, process_name};
+use crate::Source;
+
+const MULTIPLEXERS: &[(&str, &str)] = &[
+    ("tmux", "tmux"),
+    ("tmux: server", "tmux"),
+    ("tmux: client", "tmux"),
+    ("screen", "screen"),
+    ("SCREEN", "screen"),
+    ("zellij", "zellij"),
+];
+// Reaching one of these means no emulator is in the process tree
+const SESSION_ROOTS: &[&str] = &["systemd", "init", "sshd", "sshd-session"];
+
+// comm is cut at 15 characters, hence "gnome-terminal-"
+const TERMINAL_NAMES: &[(&str, &str)] = &[
+    ("gnome-terminal-", "GNOME Terminal"),
+    ("kgx", "GNOME Console"),
+    ("ptyxis-agent", "Ptyxis"),
+    ("konsole", "Konsole"),
+    ("yakuake", "Yakuake"),
+    ("kitty", "kitty"),
+    ("alacritty", "Alacritty"),
+    ("wezterm-gui", "WezTerm"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("ghostty", "Ghostty"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("mate-terminal", "MATE Terminal"),
+    ("lxterminal", "LXTerminal"),
+    ("qterminal", "QTerminal"),
+    ("tilix", "Tilix"),
+    ("terminator", "Terminator"),
+    ("guake", "Guake"),
+    ("xterm", "XTerm"),
+    ("urxvt", "rxvt-unicode"),
+    ("urxvtd", "rxvt-unicode"),
+    ("code", "VS Code"),
+];
+
+// Variables emulators set for their children. They name the emulator
+// exactly, so they are checked before the process tree, which also works
+// over SSH and in WSL. A trailing underscore matches any variable with
+// that prefix.
+const TERMINAL_VARIABLES: &[(&str, &str)] = &[
+    ("KITTY_WINDOW_ID", "kitty"),
+    ("WT_SESSION", "Windows Terminal"),
+    ("ALACRITTY_", "Alacritty"),
+    ("KONSOLE_VERSION", "Konsole"),
+    ("GNOME_TERMINAL_SCREEN", "GNOME Terminal"),
+    ("WEZTERM_EXECUTABLE", "WezTerm"),
+    ("GHOSTTY_RESOURCES_DIR", "Ghostty"),
+    ("TILIX_ID", "Tilix"),
+    ("TERMINATOR_UUID", "Terminator"),
+];
+
+#[derive(Debug, Clone)]
+pub struct TerminalInfo {
+    /// Terminal emulator, or the value of $TERM when it cannot be found
+    pub name: String,
+    /// tmux, screen or zellij running between the emulator and the shell
+    pub multiplexer: Option<String>,
+    pub term: Option<String>,
+}
+
+impl ModuleValue for TerminalInfo {
+    fn summary(&self) -> String {
+        match &self.multiplexer {
+            Some(multiplexer) => format!("{} ({})", self.name, multiplexer),
+            None => self.name.clone(),
+        }
+    }
+
+    fn rows(&self, label: &str) -> Vec<(String, String)> {
+        let mut rows = vec![(label.to_string(), self.name.clone())];
+        if let Some(multiplexer) = &self.multiplexer {
+            rows.push(("Multiplexer".to_string(), multiplexer.clone()));
+        }
+        rows
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "multiplexer": self.multiplexer,
+            "term": self.term,
+            "formatted": self.summary()
+        })
+    }
+}
+
+pub struct TerminalModule;
+
+impl Module for TerminalModule {
+    type Value = TerminalInfo;
+
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn label(&self) -> &'static str {
+        "Terminal"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<TerminalInfo> {
+        let source = &ctx.source;
+        let term = std::env::var("TERM").ok().filter(|term| !term.is_empty());
+
+        let mut walk = parent_pid(source, "self")
+            .map(|parent| walk_ancestors(source, parent))
+            .unwrap_or_default();
+
+        // The tmux server is a daemon; the emulator is an ancestor of the
+        // client attached to it
+        if walk.terminal.is_none() && walk.multiplexer.as_deref() == Some("tmux") {
+            walk.terminal = tmux_client_terminal(source);
+        }
+
+        let multiplexer = walk.multiplexer.or_else(multiplexer_from_env);
+        let name = terminal_from_env()
+            .or(walk.terminal)
+            .or_else(|| term.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Some(TerminalInfo { name, multiplexer, term })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Walk {
+    terminal: Option<String>,
+    multiplexer: Option<String>,
+}
+
+/// Follows parent processes from `pid` up to the first known terminal
+/// emulator, noting multiplexers on the way. Anything else, such as an
+/// editor, make or ssh, is passed over.
+fn walk_ancestors(source: &Source, mut pid: u32) -> Walk {
+    let mut walk = Walk::default();
+
+    // A process tree is never this deep; guards against reading a changing
+    // /proc in circles
+    for _ in 0..64 {
+        if pid <= 1 {
+            break;
+        }
+        let Some(comm) = process_name(source, pid) else {
+            break;
+        };
+
+        if let Some(&(_, multiplexer)) = MULTIPLEXERS.iter().find(|(name, _)| *name == comm) {
+            walk.multiplexer.get_or_insert_with(|| multiplexer.to_string());
+        } else if SESSION_ROOTS.contains(&comm.as_str()) {
+            break;
+        } else if let Some(terminal) = terminal_name(&comm) {
+            walk.terminal = Some(terminal);
+            break;
+        }
+
+        let Some(parent) = parent_pid(source, &pid.to_string()) else {
+            break;
+        };
+        pid = parent;
+    }
+
+    walk
+}
+
+fn tmux_client_terminal(source: &Source) -> Option<String> {
+    source.read_dir("/proc").ok()?
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str()?.parse::<u32>().ok())
+        .filter(|&pid| process_name(source, pid).as_deref() == Some("tmux"))
+        .find_map(|client| walk_ancestors(source, parent_pid(source, &client.to_string())?).terminal)
+}
+
+fn terminal_name(comm: &str) -> Option<String> {
+    TERMINAL_NAMES.iter()
+        .find(|(name, _)| *name == comm)
+        .map(|(_, pretty)| pretty.to_string())
+}
+
+fn terminal_from_env() -> Option<String> {
+    let is_set = |var: &str| match var.strip_suffix('_') {
+        Some(_) => std::env::vars_os().any(|(name, _)| name.to_string_lossy().starts_with(var)),
+        None => std::env::var_os(var).is_some(),
+    };
+    if let Some(&(_, name)) = TERMINAL_VARIABLES.iter().find(|(var, _)| is_set(var)) {
+        return Some(name.to_string());
+    }
+
+    // Set by iTerm2, VS Code, WezTerm and others; tmux overwrites it
+    std::env::var("TERM_PROGRAM")
+        .ok()
+        .filter(|program| !program.is_empty() && program != "tmux")
+}
+
+fn multiplexer_from_env() -> Option<String> {
+    [("TMUX", "tmux"), ("STY", "screen"), ("ZELLIJ", "zellij")]
+        .iter()
+        .find(|(var, _)| std::env::var_os(var).is_some())
+        .map(|(_, multiplexer)| multiplexer.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    /// Processes as (pid, comm, parent pid).
+    fn process_tree(processes: &[(u32, &str, u32)]) -> Fixture {
+        let fixture = Fixture::new();
+        for &(pid, comm, parent) in processes {
+            fixture
+                .file(&format!("/proc/{}/comm", pid), format!("{}\n", comm))
+                .file(&format!("/proc/{}/stat", pid), format!("{} ({}) S {} {} {} 0\n", pid, comm, parent, pid, pid));
+        }
+        fixture
+    }
+
+    #[test]
+    fn walks_past_programs_that_are_not_terminals() {
+        let fixture = process_tree(&[
+            (500, "bash", 400),
+            (400, "node", 300),
+            (300, "make", 200),
+            (200, "zsh", 100),
+            (100, "kitty", 1),
+        ]);
+
+        let walk = walk_ancestors(&fixture.source(), 500);
+        assert_eq!(walk.terminal.as_deref(), Some("kitty"));
+        assert_eq!(walk.multiplexer, None);
+    }
+
+    #[test]
+    fn notes_multiplexers_on_the_way() {
+        let fixture = process_tree(&[
+            (300, "zsh", 200),
+            (200, "tmux: server", 1),
+        ]);
+
+        let walk = walk_ancestors(&fixture.source(), 300);
+        assert_eq!(walk.terminal, None);
+        assert_eq!(walk.multiplexer.as_deref(), Some("tmux"));
+    }
+
+    #[test]
+    fn stops_at_session_roots() {
+        let fixture = process_tree(&[
+            (300, "bash", 200),
+            (200, "sshd-session", 100),
+            (100, "gnome-terminal-", 1),
+        ]);
+
+        assert_eq!(walk_ancestors(&fixture.source(), 300).terminal, None);
+    }
+}
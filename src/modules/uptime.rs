@@ -0,0 +1,58 @@
+use sysinfo::System;
+use crate::module::{Context, Module, ModuleValue};
+
+#[derive(Debug, Clone)]
+pub struct UptimeInfo {
+    pub seconds: u64,
+}
+
+impl UptimeInfo {
+    pub fn format_uptime(&self) -> String {
+        let days = self.seconds / 86400;
+        let hours = (self.seconds % 86400) / 3600;
+        let minutes = (self.seconds % 3600) / 60;
+
+        if days > 0 {
+            format!("{} days, {} hours, {} minutes", days, hours, minutes)
+        } else if hours > 0 {
+            format!("{} hours, {} minutes", hours, minutes)
+        } else {
+            format!("{} minutes", minutes)
+        }
+    }
+}
+
+impl ModuleValue for UptimeInfo {
+    fn summary(&self) -> String {
+        self.format_uptime()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "seconds": self.seconds,
+            "formatted": self.format_uptime()
+        })
+    }
+}
+
+pub struct UptimeModule;
+
+impl Module for UptimeModule {
+    type Value = UptimeInfo;
+
+    fn name(&self) -> &'static str {
+        "uptime"
+    }
+
+    fn label(&self) -> &'static str {
+        "Uptime"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, _ctx: &Context) -> Option<UptimeInfo> {
+        Some(UptimeInfo { seconds: System::uptime() })
+    }
+}
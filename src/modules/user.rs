@@ -0,0 +1,23 @@
+use crate::module::{Context, Module};
+
+pub struct UserModule;
+
+impl Module for UserModule {
+    type Value = String;
+
+    fn name(&self) -> &'static str {
+        "username"
+    }
+
+    fn label(&self) -> &'static str {
+        "User"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, _ctx: &Context) -> Option<String> {
+        Some(std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()))
+    }
+}
@@ -0,0 +1,241 @@
+use crate::module::{Context, Module, ModuleValue};
+use crate::Source;
+
+// DMI sys_vendor or product_name -> hypervisor
+const HYPERVISORS: &[(&str, &str)] = &[
+    ("QEMU", "QEMU"),
+    ("KVM", "KVM"),
+    ("VMware", "VMware"),
+    ("VirtualBox", "VirtualBox"),
+    ("innotek GmbH", "VirtualBox"),
+    ("Virtual Machine", "Hyper-V"),
+    ("Xen", "Xen"),
+    ("Amazon EC2", "Amazon EC2"),
+    ("Google Compute Engine", "Google Compute Engine"),
+    ("Parallels", "Parallels"),
+    ("Bochs", "Bochs"),
+    ("BHYVE", "bhyve"),
+    ("Apple Virtualization", "Apple Virtualization"),
+];
+
+// Reported when the CPU flags a hypervisor that names itself nowhere; a
+// container line leaves it out rather than say "in Unknown hypervisor"
+const UNKNOWN_HYPERVISOR: &str = "Unknown hypervisor";
+
+// Value of container= (systemd's convention) or /run/systemd/container
+const CONTAINER_NAMES: &[(&str, &str)] = &[
+    ("docker", "Docker"),
+    ("podman", "Podman"),
+    ("lxc", "LXC"),
+    ("lxc-libvirt", "LXC"),
+    ("systemd-nspawn", "systemd-nspawn"),
+    ("oci", "OCI"),
+    ("wsl", "WSL"),
+];
+
+// Markers in /proc/1/cgroup, for runtimes that set none of the above
+const CGROUP_MARKERS: &[(&str, &str)] = &[
+    ("/docker/", "Docker"),
+    ("/docker-", "Docker"),
+    ("/libpod-", "Podman"),
+    ("/lxc/", "LXC"),
+    ("/lxc.payload", "LXC"),
+    ("kubepods", "Kubernetes"),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct VirtInfo {
+    /// Hypervisor of the virtual machine zfetch runs in
+    pub hypervisor: Option<String>,
+    /// Container runtime, when running in a container
+    pub container: Option<String>,
+}
+
+impl VirtInfo {
+    /// Probes the running system. Only meaningful for the host source.
+    pub fn detect(source: &Source) -> Self {
+        Self {
+            hypervisor: hypervisor(source),
+            container: container(source),
+        }
+    }
+
+    /// Only the container runtime, without the hypervisor probes.
+    pub(crate) fn detect_container(source: &Source) -> Option<String> {
+        container(source)
+    }
+
+    fn format_virt(&self) -> String {
+        match (&self.hypervisor, &self.container) {
+            (Some(hypervisor), Some(container)) if hypervisor != UNKNOWN_HYPERVISOR => {
+                format!("{} in {}", container, hypervisor)
+            }
+            (_, Some(container)) => container.clone(),
+            (Some(hypervisor), None) => hypervisor.clone(),
+            (None, None) => "Bare metal".to_string(),
+        }
+    }
+}
+
+impl ModuleValue for VirtInfo {
+    fn summary(&self) -> String {
+        self.format_virt()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "hypervisor": self.hypervisor,
+            "container": self.container,
+            "formatted": self.format_virt()
+        })
+    }
+}
+
+pub struct VirtualizationModule;
+
+impl Module for VirtualizationModule {
+    type Value = VirtInfo;
+
+    fn name(&self) -> &'static str {
+        "virtualization"
+    }
+
+    fn label(&self) -> &'static str {
+        "Host/Virt"
+    }
+
+    fn host_only(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &Context) -> Option<VirtInfo> {
+        Some(VirtInfo::detect(&ctx.source))
+    }
+}
+
+fn hypervisor(source: &Source) -> Option<String> {
+    // WSL2 is a Hyper-V VM, but its kernel is the better tell
+    let kernel = source.read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+    if kernel.to_lowercase().contains("microsoft") {
+        return Some(if kernel.contains("WSL2") { "WSL2" } else { "WSL" }.to_string());
+    }
+
+    let dmi = |file: &str| {
+        source.read_to_string(format!("/sys/class/dmi/id/{}", file))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let (vendor, product) = (dmi("sys_vendor"), dmi("product_name"));
+    let found = HYPERVISORS.iter()
+        .find(|(marker, _)| vendor.contains(marker) || product.contains(marker));
+    if let Some((_, hypervisor)) = found {
+        return Some(hypervisor.to_string());
+    }
+
+    // Xen PV guests have no DMI tables
+    if let Ok(hypervisor) = source.read_to_string("/sys/hypervisor/type") {
+        if hypervisor.trim() == "xen" {
+            return Some("Xen".to_string());
+        }
+    }
+
+    // QEMU's generic ARM machine
+    if let Ok(compatible) = source.read_to_string("/proc/device-tree/compatible") {
+        if compatible.contains("linux,dummy-virt") {
+            return Some("QEMU".to_string());
+        }
+    }
+
+    // The CPU says it is virtualized but the hypervisor hides its name
+    let cpuinfo = source.read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let virtualized = cpuinfo.lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    virtualized.then(|| UNKNOWN_HYPERVISOR.to_string())
+}
+
+fn container(source: &Source) -> Option<String> {
+    // Markers of the container zfetch runs in, not of an installed root
+    if source.runtime_path("/.dockerenv").exists() {
+        return Some("Docker".to_string());
+    }
+    if source.runtime_path("/run/.containerenv").exists() {
+        return Some("Podman".to_string());
+    }
+
+    // systemd writes what container= said for tools that cannot read
+    // pid 1's environment, which needs root
+    let declared = source.read_to_string("/run/systemd/container")
+        .ok()
+        .or_else(|| {
+            let environ = source.read("/proc/1/environ").ok()?;
+            String::from_utf8_lossy(&environ)
+                .split('\0')
+                .find_map(|variable| variable.strip_prefix("container=").map(str::to_string))
+        });
+    if let Some(declared) = declared.map(|value| value.trim().to_string()).filter(|value| !value.is_empty()) {
+        return Some(
+            CONTAINER_NAMES.iter()
+                .find(|(name, _)| *name == declared)
+                .map(|(_, pretty)| pretty.to_string())
+                .unwrap_or(declared)
+        );
+    }
+
+    let cgroup = source.read_to_string("/proc/1/cgroup").unwrap_or_default();
+    CGROUP_MARKERS.iter()
+        .find(|(marker, _)| cgroup.contains(marker))
+        .map(|(_, runtime)| runtime.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn virt(hypervisor: Option<&str>, container: Option<&str>) -> VirtInfo {
+        VirtInfo {
+            hypervisor: hypervisor.map(str::to_string),
+            container: container.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn names_the_container_and_its_hypervisor() {
+        assert_eq!(virt(Some("KVM"), Some("Docker")).format_virt(), "Docker in KVM");
+        assert_eq!(virt(Some(UNKNOWN_HYPERVISOR), Some("Docker")).format_virt(), "Docker");
+        assert_eq!(virt(Some(UNKNOWN_HYPERVISOR), None).format_virt(), UNKNOWN_HYPERVISOR);
+        assert_eq!(virt(None, None).format_virt(), "Bare metal");
+    }
+
+    #[test]
+    fn falls_back_to_the_cpu_hypervisor_flag() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/proc/sys/kernel/osrelease", "6.9.7-arch1-1\n")
+            .file("/sys/class/dmi/id/sys_vendor", "Example Cloud\n")
+            .file("/proc/cpuinfo", "processor\t: 0\nflags\t\t: fpu vme hypervisor lahf_lm\n");
+        assert_eq!(hypervisor(&fixture.source()).as_deref(), Some(UNKNOWN_HYPERVISOR));
+
+        fixture.file("/sys/class/dmi/id/product_name", "KVM\n");
+        assert_eq!(hypervisor(&fixture.source()).as_deref(), Some("KVM"));
+    }
+
+    #[test]
+    fn bare_metal_has_no_hypervisor() {
+        let fixture = Fixture::new();
+        fixture.file("/proc/cpuinfo", "processor\t: 0\nflags\t\t: fpu vme lahf_lm\n");
+        assert_eq!(hypervisor(&fixture.source()), None);
+    }
+
+    #[test]
+    fn reads_container_markers_from_the_runtime_root() {
+        let fixture = Fixture::new();
+        fixture.file("/.dockerenv", "");
+
+        // An installed root's /.dockerenv says nothing about where zfetch runs
+        let installed = Source::installed(fixture.root());
+        assert_eq!(container(&installed).as_deref(), container(&Source::host()).as_deref());
+        assert_eq!(container(&fixture.source()).as_deref(), Some("Docker"));
+    }
+}
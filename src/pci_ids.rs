@@ -0,0 +1,243 @@
+//! Lookup of PCI vendor, device and subsystem names in the `pci.ids`
+//! database shipped by hwdata/pciutils, with a small built-in table for
+//! systems that have neither.
+
+use std::borrow::Cow;
+use crate::Source;
+
+const DATABASE_PATHS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pciutils/pci.ids",
+];
+
+// Subset of pci.ids covering common GPUs, in the same format
+const BUNDLED: &str = "\
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t15d8  Picasso/Raven 2 [Radeon Vega Series / Radeon Vega Mobile Series]
+\t1681  Rembrandt [Radeon 680M]
+\t67df  Ellesmere [Radeon RX 470/480/570/570X/580/580X/590]
+\t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+\t73df  Navi 22 [Radeon RX 6700/6700 XT/6750 XT / 6800M/6850M XT]
+\t73ff  Navi 23 [Radeon RX 6600/6600 XT/6600M]
+\t744c  Navi 31 [Radeon RX 7900 XT/7900 XTX/7900 GRE/7900M]
+10de  NVIDIA Corporation
+\t1b80  GP104 [GeForce GTX 1080]
+\t1b81  GP104 [GeForce GTX 1070]
+\t1c03  GP106 [GeForce GTX 1060 6GB]
+\t1c82  GP107 [GeForce GTX 1050 Ti]
+\t2204  GA102 [GeForce RTX 3090]
+\t2206  GA102 [GeForce RTX 3080]
+\t2484  GA104 [GeForce RTX 3070]
+\t2503  GA106 [GeForce RTX 3060]
+\t2684  AD102 [GeForce RTX 4090]
+1234  Technical Corp.
+\t1111  QEMU Virtual Video Controller
+15ad  VMware
+\t0405  SVGA II Adapter
+1a03  ASPEED Technology, Inc.
+\t2000  ASPEED Graphics Family
+1af4  Red Hat, Inc.
+\t1050  Virtio 1.0 GPU
+1b36  Red Hat, Inc.
+\t0100  QXL paravirtual graphic card
+80ee  InnoTek Systemberatung GmbH
+\tbeef  VirtualBox Graphics Adapter
+8086  Intel Corporation
+\t3e92  CoffeeLake-S GT2 [UHD Graphics 630]
+\t3e9b  CoffeeLake-H GT2 [UHD Graphics 630]
+\t46a6  Alder Lake-P GT2 [Iris Xe Graphics]
+\t56a0  DG2 [Arc A770]
+\t5917  UHD Graphics 620
+\t9a49  TigerLake-LP GT2 [Iris Xe Graphics]
+\ta780  Raptor Lake-S GT1 [UHD Graphics 770]
+";
+
+/// Numeric identity of a PCI function as found in sysfs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciId {
+    pub vendor: u16,
+    pub device: u16,
+    /// Subsystem vendor and device, identifying the board maker's variant.
+    pub subsystem: Option<(u16, u16)>,
+}
+
+/// Names found for a `PciId`; each part is looked up independently.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PciName {
+    pub vendor: Option<String>,
+    pub device: Option<String>,
+    pub subsystem: Option<String>,
+}
+
+/// One or more pci.ids databases, queried in order.
+#[derive(Debug, Clone)]
+pub struct PciIds {
+    databases: Vec<Cow<'static, str>>,
+}
+
+impl PciIds {
+    /// The system database under `source`, if any, backed by the bundled
+    /// table for devices it does not know.
+    pub fn load(source: &Source) -> Self {
+        let mut databases: Vec<Cow<'static, str>> = DATABASE_PATHS.iter()
+            .find_map(|path| source.read_to_string(path).ok())
+            .map(Cow::Owned)
+            .into_iter()
+            .collect();
+        databases.push(Cow::Borrowed(BUNDLED));
+
+        Self { databases }
+    }
+
+    /// Only the built-in table.
+    pub fn bundled() -> Self {
+        Self::from_contents(BUNDLED)
+    }
+
+    pub fn from_contents(contents: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            databases: vec![contents.into()],
+        }
+    }
+
+    pub fn lookup(&self, id: &PciId) -> PciName {
+        let mut name = PciName::default();
+
+        for database in &self.databases {
+            let found = lookup_in(database, id);
+            name.vendor = name.vendor.or(found.vendor);
+            name.device = name.device.or(found.device);
+            name.subsystem = name.subsystem.or(found.subsystem);
+
+            if name.device.is_some() && (id.subsystem.is_none() || name.subsystem.is_some()) {
+                break;
+            }
+        }
+
+        name
+    }
+}
+
+/// Scans a single database. The file is sorted by vendor and only the
+/// matching vendor block is walked, so there is no need to build an index.
+fn lookup_in(contents: &str, id: &PciId) -> PciName {
+    let mut name = PciName::default();
+    let mut in_vendor = false;
+    let mut in_device = false;
+
+    for line in contents.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("\t\t") {
+            // "ssss dddd  name" for the current device
+            if in_device {
+                if let (Some((sub_vendor, sub_device)), Some(subsystem)) = (id.subsystem, split_entry(rest)) {
+                    let mut ids = subsystem.0.split(' ');
+                    if parse_id(ids.next()) == Some(sub_vendor) && parse_id(ids.next()) == Some(sub_device) {
+                        name.subsystem = Some(subsystem.1.to_string());
+                    }
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix('\t') {
+            if in_vendor {
+                in_device = match split_entry(rest) {
+                    Some((device, device_name)) if parse_id(Some(device)) == Some(id.device) => {
+                        name.device = Some(device_name.to_string());
+                        true
+                    }
+                    _ => false,
+                };
+            }
+        } else if in_vendor {
+            // Next vendor, or the device class section at the end
+            break;
+        } else if let Some((vendor, vendor_name)) = split_entry(line) {
+            if parse_id(Some(vendor)) == Some(id.vendor) {
+                name.vendor = Some(vendor_name.to_string());
+                in_vendor = true;
+            }
+        }
+    }
+
+    name
+}
+
+/// Splits "id  name" on the double space separating the two.
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    line.split_once("  ")
+        .map(|(id, name)| (id.trim(), name.trim()))
+}
+
+fn parse_id(value: Option<&str>) -> Option<u16> {
+    u16::from_str_radix(value?.trim().trim_start_matches("0x"), 16).ok()
+}
+
+/// Reads a "0x10de"-style id file from sysfs.
+pub fn read_sysfs_id(source: &Source, path: impl AsRef<std::path::Path>) -> Option<u16> {
+    parse_id(Some(&source.read_to_string(path).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const DATABASE: &str = "\
+# List of PCI ID's
+10de  NVIDIA Corporation
+\t2684  AD102 [GeForce RTX 4090]
+\t\t1043 889d  ROG Strix GeForce RTX 4090
+\t\t1458 4104  GeForce RTX 4090 Gaming OC
+1af4  Red Hat, Inc.
+
+# List of known device classes
+C 03  Display controller
+\t00  VGA compatible controller
+";
+
+    fn id(vendor: u16, device: u16, subsystem: Option<(u16, u16)>) -> PciId {
+        PciId { vendor, device, subsystem }
+    }
+
+    #[test]
+    fn looks_up_vendor_device_and_subsystem() {
+        let name = PciIds::from_contents(DATABASE).lookup(&id(0x10de, 0x2684, Some((0x1043, 0x889d))));
+        assert_eq!(name.vendor.as_deref(), Some("NVIDIA Corporation"));
+        assert_eq!(name.device.as_deref(), Some("AD102 [GeForce RTX 4090]"));
+        assert_eq!(name.subsystem.as_deref(), Some("ROG Strix GeForce RTX 4090"));
+    }
+
+    #[test]
+    fn stops_at_the_next_vendor() {
+        // 2684 is not a Red Hat device even though it follows in the file
+        let name = PciIds::from_contents(DATABASE).lookup(&id(0x1af4, 0x2684, None));
+        assert_eq!(name.vendor.as_deref(), Some("Red Hat, Inc."));
+        assert_eq!(name.device, None);
+    }
+
+    #[test]
+    fn falls_back_to_the_bundled_table() {
+        let fixture = Fixture::new();
+        fixture.file("/usr/share/misc/pci.ids", DATABASE);
+        let ids = PciIds::load(&fixture.source());
+
+        let known = ids.lookup(&id(0x10de, 0x2684, Some((0x1458, 0x4104))));
+        assert_eq!(known.subsystem.as_deref(), Some("GeForce RTX 4090 Gaming OC"));
+        let bundled = ids.lookup(&id(0x8086, 0x56a0, None));
+        assert_eq!(bundled.vendor.as_deref(), Some("Intel Corporation"));
+        assert_eq!(bundled.device.as_deref(), Some("DG2 [Arc A770]"));
+    }
+
+    #[test]
+    fn reads_sysfs_ids() {
+        let fixture = Fixture::new();
+        fixture.file("/sys/bus/pci/devices/0000:01:00.0/vendor", "0x10de\n");
+        let source = fixture.source();
+        assert_eq!(read_sysfs_id(&source, "/sys/bus/pci/devices/0000:01:00.0/vendor"), Some(0x10de));
+        assert_eq!(read_sysfs_id(&source, "/sys/bus/pci/devices/0000:01:00.0/device"), None);
+    }
+}
@@ -0,0 +1,397 @@
+//! Parser for the raw SMBIOS table the kernel exposes in
+//! /sys/firmware/dmi/tables/DMI.
+//!
+//! /sys/class/dmi/id only carries the system, board and BIOS strings; the
+//! memory modules are only described in the table itself. Reading it needs
+//! root, so everything built on it is optional.
+
+use crate::Source;
+
+const TABLE_PATH: &str = "/sys/firmware/dmi/tables/DMI";
+
+const CHASSIS: u8 = 3;
+const MEMORY_DEVICE: u8 = 17;
+const END_OF_TABLE: u8 = 127;
+
+// Size value meaning "see the extended size field"
+const EXTENDED_SIZE: u16 = 0x7fff;
+const UNKNOWN: u16 = 0xffff;
+
+/// Form factor from the chassis type, coarser than SMBIOS's 36 values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chassis {
+    Desktop,
+    Laptop,
+    Convertible,
+    Tablet,
+    Server,
+    Other,
+}
+
+impl Chassis {
+    /// Maps an SMBIOS chassis type, as also found in
+    /// /sys/class/dmi/id/chassis_type.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Some(match code & 0x7f {
+            // Desktop, low profile, pizza box, mini tower, tower, all in
+            // one, space-saving, lunch box, mini PC, stick PC
+            0x03..=0x07 | 0x0d | 0x0f | 0x10 | 0x23 | 0x24 => Self::Desktop,
+            // Portable, laptop, notebook, sub notebook
+            0x08..=0x0a | 0x0e => Self::Laptop,
+            // Convertible, detachable
+            0x1f | 0x20 => Self::Convertible,
+            // Hand held, tablet
+            0x0b | 0x1e => Self::Tablet,
+            // Main server chassis, rack mount, blade, blade enclosure
+            0x11 | 0x17 | 0x1c | 0x1d => Self::Server,
+            // Other and Unknown say nothing
+            0x01 | 0x02 => return None,
+            _ => Self::Other,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Laptop => "Laptop",
+            Self::Convertible => "Convertible",
+            Self::Tablet => "Tablet",
+            Self::Server => "Server",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// A populated memory slot (SMBIOS type 17).
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDevice {
+    /// Slot name such as "DIMM A1" or "ChannelA-DIMM0"
+    pub locator: Option<String>,
+    pub bank: Option<String>,
+    /// Size in bytes
+    pub size: u64,
+    /// DDR4, DDR5, LPDDR5...
+    pub memory_type: Option<&'static str>,
+    /// Rated speed in MT/s
+    pub speed: Option<u32>,
+    /// Speed the module currently runs at, in MT/s
+    pub configured_speed: Option<u32>,
+    pub manufacturer: Option<String>,
+    pub part_number: Option<String>,
+}
+
+/// One structure of the table: its formatted area and its strings.
+struct Structure<'a> {
+    kind: u8,
+    data: &'a [u8],
+    strings: Vec<&'a [u8]>,
+}
+
+impl Structure<'_> {
+    fn byte(&self, offset: usize) -> Option<u8> {
+        self.data.get(offset).copied()
+    }
+
+    fn word(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn dword(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// String referenced by the index byte at `offset`; 0 means none.
+    fn string(&self, offset: usize) -> Option<String> {
+        let index = self.byte(offset)? as usize;
+        let string = String::from_utf8_lossy(self.strings.get(index.checked_sub(1)?)?);
+        let string = string.trim();
+        let placeholder = string.is_empty()
+            || ["not specified", "unknown", "undefined"].contains(&string.to_lowercase().as_str());
+        (!placeholder).then(|| string.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Smbios {
+    table: Vec<u8>,
+}
+
+impl Smbios {
+    pub fn read(source: &Source) -> Option<Self> {
+        source.read(TABLE_PATH).ok().map(Self::from_bytes)
+    }
+
+    /// Table from a captured DMI file, e.g. `dmidecode --dump-bin` output
+    /// without its 32 byte entry point.
+    pub fn from_bytes(table: Vec<u8>) -> Self {
+        Self { table }
+    }
+
+    pub fn chassis(&self) -> Option<Chassis> {
+        self.structures()
+            .find(|structure| structure.kind == CHASSIS)
+            .and_then(|chassis| Chassis::from_code(chassis.byte(0x05)?))
+    }
+
+    /// Populated memory slots; empty ones are left out.
+    pub fn memory_devices(&self) -> Vec<MemoryDevice> {
+        self.structures()
+            .filter(|structure| structure.kind == MEMORY_DEVICE)
+            .filter_map(|device| memory_device(&device))
+            .collect()
+    }
+
+    fn structures(&self) -> impl Iterator<Item = Structure<'_>> {
+        let table = &self.table;
+        let mut offset = 0;
+
+        std::iter::from_fn(move || {
+            // Type, length, handle
+            let header = table.get(offset..offset + 4)?;
+            let (kind, length) = (header[0], header[1] as usize);
+            if kind == END_OF_TABLE || length < 4 {
+                return None;
+            }
+            let data = table.get(offset..offset + length)?;
+
+            // The string set ends with two NULs; an empty set is just those
+            let strings_start = offset + length;
+            let strings_length = table.get(strings_start..)?
+                .windows(2)
+                .position(|pair| pair == [0, 0])?;
+            let strings = table[strings_start..strings_start + strings_length]
+                .split(|&byte| byte == 0)
+                .filter(|string| !string.is_empty())
+                .collect();
+
+            offset = strings_start + strings_length + 2;
+            Some(Structure { kind, data, strings })
+        })
+    }
+}
+
+fn memory_device(device: &Structure) -> Option<MemoryDevice> {
+    // Bit 15 set means the size is in KB rather than MB
+    let size = match device.word(0x0c)? {
+        0 | UNKNOWN => return None,
+        EXTENDED_SIZE => device.dword(0x1c)? as u64 * 1024 * 1024,
+        size if size & 0x8000 != 0 => (size & 0x7fff) as u64 * 1024,
+        size => size as u64 * 1024 * 1024,
+    };
+    let speed = |offset: usize, extended: usize| match device.word(offset)? {
+        0 => None,
+        UNKNOWN => device.dword(extended).filter(|&speed| speed > 0),
+        speed => Some(speed as u32),
+    };
+
+    Some(MemoryDevice {
+        locator: device.string(0x10),
+        bank: device.string(0x11),
+        size,
+        memory_type: device.byte(0x12).and_then(memory_type),
+        speed: speed(0x15, 0x54),
+        configured_speed: speed(0x20, 0x58),
+        manufacturer: device.string(0x17),
+        part_number: device.string(0x1a),
+    })
+}
+
+fn memory_type(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x12 => "DDR",
+        0x13 => "DDR2",
+        0x14 => "DDR2 FB-DIMM",
+        0x18 => "DDR3",
+        0x1a => "DDR4",
+        0x1b => "LPDDR",
+        0x1c => "LPDDR2",
+        0x1d => "LPDDR3",
+        0x1e => "LPDDR4",
+        0x20 => "HBM",
+        0x21 => "HBM2",
+        0x22 => "DDR5",
+        0x23 => "LPDDR5",
+        0x24 => "HBM3",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A structure of `length` bytes with `fields` written at their
+    /// offsets, followed by its string set.
+    fn structure(kind: u8, length: u8, fields: &[(usize, &[u8])], strings: &[&str]) -> Vec<u8> {
+        let mut data = vec![0u8; length as usize];
+        data[0] = kind;
+        data[1] = length;
+        for (offset, bytes) in fields {
+            data[*offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+
+        for string in strings {
+            data.extend_from_slice(string.as_bytes());
+            data.push(0);
+        }
+        // The set always ends with an extra NUL; an empty one is two NULs
+        if strings.is_empty() {
+            data.push(0);
+        }
+        data.push(0);
+        data
+    }
+
+    /// A DDR5 memory device of SMBIOS 3.3 length.
+    fn memory(size: u16, fields: &[(usize, &[u8])], strings: &[&str]) -> Vec<u8> {
+        let size = size.to_le_bytes();
+        let mut all: Vec<(usize, &[u8])> = vec![(0x0c, &size), (0x12, &[0x22])];
+        all.extend_from_slice(fields);
+        structure(MEMORY_DEVICE, 0x5c, &all, strings)
+    }
+
+    fn table(structures: &[Vec<u8>]) -> Smbios {
+        let mut table = structures.concat();
+        table.extend(structure(END_OF_TABLE, 4, &[], &[]));
+        Smbios::from_bytes(table)
+    }
+
+    #[test]
+    fn reads_memory_devices() {
+        let smbios = table(&[memory(
+            16384,
+            &[(0x10, &[1]), (0x11, &[2]), (0x15, &5600u16.to_le_bytes()), (0x17, &[3]), (0x1a, &[4]),
+              (0x20, &4800u16.to_le_bytes())],
+            &["DIMM A1", "BANK 0", "Kingston", "KF556C40-16  "],
+        )]);
+
+        let devices = smbios.memory_devices();
+        assert_eq!(devices.len(), 1);
+        let device = &devices[0];
+        assert_eq!(device.locator.as_deref(), Some("DIMM A1"));
+        assert_eq!(device.bank.as_deref(), Some("BANK 0"));
+        assert_eq!(device.size, 16 << 30);
+        assert_eq!(device.memory_type, Some("DDR5"));
+        assert_eq!((device.speed, device.configured_speed), (Some(5600), Some(4800)));
+        assert_eq!(device.manufacturer.as_deref(), Some("Kingston"));
+        assert_eq!(device.part_number.as_deref(), Some("KF556C40-16"));
+    }
+
+    #[test]
+    fn reads_extended_sizes_and_speeds() {
+        let smbios = table(&[
+            // 64 GB only fits the extended size, in MB
+            memory(EXTENDED_SIZE, &[(0x1c, &65536u32.to_le_bytes())], &[]),
+            // Bit 15 switches the size to KB
+            memory(0x8000 | 512, &[], &[]),
+            // Speeds from 65535 MT/s up live in the extended fields
+            memory(
+                8192,
+                &[(0x15, &UNKNOWN.to_le_bytes()), (0x54, &70000u32.to_le_bytes()),
+                  (0x20, &UNKNOWN.to_le_bytes()), (0x58, &66000u32.to_le_bytes())],
+                &[],
+            ),
+        ]);
+
+        let devices = smbios.memory_devices();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].size, 64 << 30);
+        assert_eq!(devices[1].size, 512 << 10);
+        assert_eq!(devices[2].speed, Some(70000));
+        assert_eq!(devices[2].configured_speed, Some(66000));
+        assert_eq!(devices[0].speed, None);
+    }
+
+    #[test]
+    fn skips_empty_slots() {
+        let smbios = table(&[
+            memory(0, &[(0x10, &[1]), (0x17, &[2])], &["DIMM B1", "No Module Installed"]),
+            memory(UNKNOWN, &[], &[]),
+            memory(8192, &[(0x17, &[1])], &["Unknown"]),
+        ]);
+
+        let devices = smbios.memory_devices();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].size, 8 << 30);
+        // Placeholder strings are dropped
+        assert_eq!(devices[0].manufacturer, None);
+    }
+
+    #[test]
+    fn walks_past_structures_without_strings() {
+        let smbios = table(&[
+            // A type 0 with no strings ends in just two NULs
+            structure(0, 0x18, &[], &[]),
+            // Notebook, with the chassis lock bit set
+            structure(CHASSIS, 0x15, &[(0x05, &[0x80 | 0x0a])], &["LENOVO"]),
+            memory(4096, &[], &[]),
+        ]);
+
+        assert_eq!(smbios.chassis(), Some(Chassis::Laptop));
+        assert_eq!(smbios.memory_devices().len(), 1);
+    }
+
+    #[test]
+    fn reads_a_captured_smbios_2_table() {
+        let smbios = Smbios::from_bytes(include_bytes!("../tests/data/dmi-thinkpad-t430.bin").to_vec());
+        assert_eq!(smbios.chassis(), Some(Chassis::Laptop));
+
+        let devices = smbios.memory_devices();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].locator.as_deref(), Some("ChannelA-DIMM0"));
+        assert_eq!(devices[1].bank.as_deref(), Some("BANK 2"));
+        for device in &devices {
+            assert_eq!(device.size, 4 << 30);
+            assert_eq!(device.memory_type, Some("DDR3"));
+            assert_eq!((device.speed, device.configured_speed), (Some(1600), Some(1600)));
+            assert_eq!(device.manufacturer.as_deref(), Some("Hynix/Hyundai"));
+            assert_eq!(device.part_number.as_deref(), Some("HMT351S6CFR8C-PB"));
+        }
+    }
+
+    #[test]
+    fn reads_a_captured_smbios_3_table() {
+        let smbios = Smbios::from_bytes(include_bytes!("../tests/data/dmi-precision-3620.bin").to_vec());
+        assert_eq!(smbios.chassis(), Some(Chassis::Desktop));
+
+        // DIMM3 and DIMM4 are empty
+        let devices = smbios.memory_devices();
+        let locators: Vec<_> = devices.iter().filter_map(|device| device.locator.as_deref()).collect();
+        assert_eq!(locators, ["DIMM1", "DIMM2"]);
+        for device in &devices {
+            assert_eq!(device.size, 8 << 30);
+            assert_eq!(device.memory_type, Some("DDR4"));
+            assert_eq!((device.speed, device.configured_speed), (Some(2133), Some(2133)));
+            assert_eq!(device.part_number.as_deref(), Some("HMA41GU6AFR8N-TF"));
+        }
+    }
+
+    #[test]
+    fn reads_a_captured_server_table() {
+        let smbios = Smbios::from_bytes(include_bytes!("../tests/data/dmi-poweredge.bin").to_vec());
+        assert_eq!(smbios.chassis(), Some(Chassis::Server));
+
+        // 8 of the 24 slots are populated
+        let devices = smbios.memory_devices();
+        assert_eq!(devices.len(), 8);
+        assert_eq!(devices[0].locator.as_deref(), Some("A1"));
+        assert_eq!(devices[2].part_number.as_deref(), Some("36ASF2G72PZ-2G1A2"));
+        for device in &devices {
+            assert_eq!(device.size, 16 << 30);
+            assert_eq!(device.memory_type, Some("DDR4"));
+            assert_eq!((device.speed, device.configured_speed), (Some(2133), Some(2133)));
+        }
+    }
+
+    #[test]
+    fn maps_chassis_codes() {
+        assert_eq!(Chassis::from_code(0x03), Some(Chassis::Desktop));
+        assert_eq!(Chassis::from_code(0x83), Some(Chassis::Desktop));
+        assert_eq!(Chassis::from_code(0x1f), Some(Chassis::Convertible));
+        assert_eq!(Chassis::from_code(0x17), Some(Chassis::Server));
+        assert_eq!(Chassis::from_code(0x02), None);
+        assert_eq!(Chassis::from_code(0x30), Some(Chassis::Other));
+    }
+}
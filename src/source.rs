@@ -0,0 +1,309 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Filesystem view used by every probe.
+///
+/// Probes always ask for absolute paths such as `/etc/os-release`; the source
+/// resolves them against its root so the same code can inspect the running
+/// system, a mounted image or a fixture directory.
+///
+/// Kernel interfaces (`/proc`, `/sys`, `/dev`, `/run`) are resolved against a
+/// separate runtime root. For fixtures both roots are the same directory; for
+/// an installed system mounted under `/mnt` the runtime root stays `/`
+/// because only the running kernel can answer those.
+#[derive(Debug, Clone)]
+pub struct Source {
+    root: PathBuf,
+    runtime_root: PathBuf,
+}
+
+const RUNTIME_DIRS: &[&str] = &["/proc", "/sys", "/dev", "/run"];
+
+// Same limit as the kernel's MAXSYMLINKS
+const MAX_SYMLINKS: usize = 40;
+
+impl Source {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self { runtime_root: root.clone(), root }
+    }
+
+    /// Source for an installed system mounted at `root`, while kernel
+    /// interfaces still come from the running host.
+    pub fn installed(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), runtime_root: PathBuf::from("/") }
+    }
+
+    /// Source rooted at `/`, i.e. the running system.
+    pub fn host() -> Self {
+        Self::new("/")
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns true when the source points at the running system.
+    pub fn is_host(&self) -> bool {
+        self.root == Path::new("/")
+    }
+
+    /// Resolves an absolute probe path against the root.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        let root = if RUNTIME_DIRS.iter().any(|dir| path.starts_with(dir)) {
+            &self.runtime_root
+        } else {
+            &self.root
+        };
+        resolve(root, path)
+    }
+
+    /// Resolves a path the running kernel reports, such as a mount point
+    /// from /proc/self/mounts, against the runtime root.
+    pub fn runtime_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        resolve(&self.runtime_root, path.as_ref())
+    }
+
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> io::Result<String> {
+        fs::read_to_string(self.path(path))
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        fs::read(self.path(path))
+    }
+
+    /// Contents of a single-value file such as a sysfs attribute, without
+    /// the trailing newline. None when it cannot be read.
+    pub fn read_trimmed(&self, path: impl AsRef<Path>) -> Option<String> {
+        Some(self.read_to_string(path).ok()?.trim().to_string())
+    }
+
+    /// Lists a directory, returning probe paths (not root-resolved ones) in
+    /// name order so repeated runs enumerate devices identically.
+    pub fn read_dir(&self, path: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+        let path = path.as_ref();
+        let mut entries: Vec<PathBuf> = fs::read_dir(self.path(path))?
+            .flatten()
+            .map(|entry| path.join(entry.file_name()))
+            .collect();
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// Target of a symlink, as stored in the link.
+    pub fn read_link(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = path.as_ref();
+        // Resolve everything but the link itself
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => fs::read_link(self.path(parent).join(name)),
+            _ => fs::read_link(self.path(path)),
+        }
+    }
+
+    pub fn open(&self, path: impl AsRef<Path>) -> io::Result<fs::File> {
+        fs::File::open(self.path(path))
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.path(path).exists()
+    }
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+fn resolve(root: &Path, path: &Path) -> PathBuf {
+    if root == Path::new("/") {
+        path.to_path_buf()
+    } else {
+        resolve_in_root(root, path)
+    }
+}
+
+/// Joins `path` onto `root` the way chroot would see it: symlinks are
+/// followed one component at a time and absolute targets or `..` never
+/// leave the root. Installed systems are full of links such as
+/// /etc/os-release -> /usr/lib/os-release that would otherwise point into
+/// the host.
+fn resolve_in_root(root: &Path, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    let mut pending: Vec<OsString> = components(path);
+    let mut links = 0;
+
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+
+        let candidate = resolved.join(&name);
+        match fs::read_link(root.join(&candidate)) {
+            Ok(target) if links < MAX_SYMLINKS => {
+                links += 1;
+                if target.is_absolute() {
+                    resolved.clear();
+                }
+                pending.extend(components(&target));
+            }
+            _ => resolved = candidate,
+        }
+    }
+
+    root.join(resolved)
+}
+
+/// Normal and parent components of `path`, last one first so they can be
+/// popped in order.
+fn components(path: &Path) -> Vec<OsString> {
+    path.components()
+        .rev()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_os_string()),
+            Component::ParentDir => Some(OsString::from("..")),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn host_paths_are_unchanged() {
+        let source = Source::host();
+        assert!(source.is_host());
+        assert_eq!(source.path("/etc/os-release"), Path::new("/etc/os-release"));
+    }
+
+    #[test]
+    fn probe_paths_resolve_under_the_root() {
+        let source = Source::new("/mnt/sysroot");
+        assert!(!source.is_host());
+        assert_eq!(source.path("/etc/hostname"), Path::new("/mnt/sysroot/etc/hostname"));
+        assert_eq!(source.path("/proc/version"), Path::new("/mnt/sysroot/proc/version"));
+    }
+
+    #[test]
+    fn reads_files_from_the_root() {
+        let fixture = Fixture::new();
+        fixture.file("/etc/hostname", "fixture-host\n");
+        let source = fixture.source();
+
+        assert_eq!(source.read_to_string("/etc/hostname").unwrap(), "fixture-host\n");
+        assert!(source.exists("/etc/hostname"));
+        assert!(!source.exists("/etc/machine-id"));
+    }
+
+    #[test]
+    fn follows_relative_links_in_the_root() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/usr/lib/os-release", "ID=arch\n")
+            .symlink("/etc/os-release", "../usr/lib/os-release");
+
+        assert_eq!(fixture.source().read_to_string("/etc/os-release").unwrap(), "ID=arch\n");
+    }
+
+    #[test]
+    fn installed_systems_keep_kernel_interfaces_on_the_host() {
+        let source = Source::installed("/mnt/sysroot");
+        assert!(!source.is_host());
+        assert_eq!(source.path("/etc/hostname"), Path::new("/mnt/sysroot/etc/hostname"));
+        assert_eq!(source.path("/usr/lib/os-release"), Path::new("/mnt/sysroot/usr/lib/os-release"));
+        assert_eq!(source.path("/proc/version"), Path::new("/proc/version"));
+        assert_eq!(source.path("/sys/class/drm"), Path::new("/sys/class/drm"));
+        assert_eq!(source.path("/run/systemd/container"), Path::new("/run/systemd/container"));
+        // Only whole components count as runtime directories
+        assert_eq!(source.path("/procfs"), Path::new("/mnt/sysroot/procfs"));
+        // Mount points in the host's mount table are host paths
+        assert_eq!(source.runtime_path("/home"), Path::new("/home"));
+        assert_eq!(Source::new("/fixture").runtime_path("/home"), Path::new("/fixture/home"));
+    }
+
+    #[test]
+    fn absolute_links_stay_inside_the_root() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/usr/lib/os-release", "ID=fixture\n")
+            .symlink("/etc/os-release", "/usr/lib/os-release");
+        let source = fixture.source();
+
+        assert_eq!(source.path("/etc/os-release"), fixture.real_path("/usr/lib/os-release"));
+        assert_eq!(source.read_to_string("/etc/os-release").unwrap(), "ID=fixture\n");
+    }
+
+    #[test]
+    fn links_in_directory_components_are_followed() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/usr/lib/modules/6.9.7-arch1-1")
+            .symlink("/lib", "usr/lib");
+
+        assert_eq!(
+            fixture.source().read_dir("/lib/modules").unwrap(),
+            [PathBuf::from("/lib/modules/6.9.7-arch1-1")]
+        );
+    }
+
+    #[test]
+    fn parent_components_cannot_escape_the_root() {
+        let fixture = Fixture::new();
+        fixture
+            .file("/usr/lib/os-release", "ID=fixture\n")
+            .symlink("/etc/os-release", "../../../../../usr/lib/os-release");
+        let source = fixture.source();
+
+        assert_eq!(source.path("/etc/os-release"), fixture.real_path("/usr/lib/os-release"));
+        assert_eq!(source.path("/../../etc/hostname"), fixture.real_path("/etc/hostname"));
+        assert_eq!(source.read_to_string("/etc/os-release").unwrap(), "ID=fixture\n");
+    }
+
+    #[test]
+    fn symlink_loops_stop_after_max_symlinks() {
+        let fixture = Fixture::new();
+        fixture
+            .symlink("/etc/a", "/etc/b")
+            .symlink("/etc/b", "a");
+        let source = fixture.source();
+
+        // The loop ends with the last link taken literally
+        let resolved = source.path("/etc/a");
+        assert!(resolved.starts_with(fixture.root()));
+        assert!(source.read_to_string("/etc/a").is_err());
+    }
+
+    #[test]
+    fn read_link_returns_the_stored_target() {
+        let fixture = Fixture::new();
+        fixture
+            .dir("/sys/devices/pci0000:00/0000:00:02.0")
+            .symlink("/sys/devices/pci0000:00/0000:00:02.0/driver", "../../../bus/pci/drivers/i915")
+            .symlink("/sys/class/drm/card0/device", "../../../devices/pci0000:00/0000:00:02.0");
+
+        // The device link in the path is followed, the driver link is not
+        assert_eq!(
+            fixture.source().read_link("/sys/class/drm/card0/device/driver").unwrap(),
+            Path::new("../../../bus/pci/drivers/i915")
+        );
+    }
+
+    #[test]
+    fn read_dir_returns_sorted_probe_paths() {
+        let fixture = Fixture::new();
+        fixture.dir("/sys/class/drm/card1").dir("/sys/class/drm/card0");
+
+        let entries = fixture.source().read_dir("/sys/class/drm").unwrap();
+        assert_eq!(entries, [
+            PathBuf::from("/sys/class/drm/card0"),
+            PathBuf::from("/sys/class/drm/card1"),
+        ]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Just enough of the SQLite file format to count the rows of a table.
+//!
+//! rpm keeps its database in SQLite these days; linking libsqlite3 (or
+//! forking `rpm -qa`) only to learn how many packages are installed would
+//! cost more than the rest of zfetch. Only the main database file is read,
+//! so changes still sitting in a write-ahead log are not seen.
+
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+
+const HEADER: &[u8] = b"SQLite format 3\0";
+// Offset of the b-tree header on page 1, which starts with the file header
+const FILE_HEADER_SIZE: usize = 100;
+
+const INTERIOR_TABLE_PAGE: u8 = 0x05;
+const LEAF_TABLE_PAGE: u8 = 0x0d;
+
+// Guards against cycles in a corrupted file
+const MAX_DEPTH: usize = 32;
+
+/// Read-only view of a database file, one page at a time.
+pub struct Database {
+    file: File,
+    page_size: usize,
+}
+
+impl Database {
+    pub fn open(file: File) -> Option<Self> {
+        let mut header = [0u8; FILE_HEADER_SIZE];
+        file.read_exact_at(&mut header, 0).ok()?;
+        if !header.starts_with(HEADER) {
+            return None;
+        }
+
+        // Stored big endian; 1 stands for 65536
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+            1 => 65536,
+            size if size >= 512 && size.is_power_of_two() => size as usize,
+            _ => return None,
+        };
+
+        Some(Self { file, page_size })
+    }
+
+    /// Number of rows in `table`, found through the schema table.
+    pub fn count_rows(&self, table: &str) -> Option<u64> {
+        let root = self.table_root(table)?;
+        let mut count = 0;
+        self.walk(root, 0, &mut |_, _| count += 1)?;
+        Some(count)
+    }
+
+    /// Root page of `table`, from the sqlite_schema table on page 1.
+    fn table_root(&self, table: &str) -> Option<u32> {
+        let mut root = None;
+        self.walk(1, 0, &mut |page, cell| {
+            if root.is_none() {
+                root = schema_root(page, cell, table);
+            }
+        })?;
+        root
+    }
+
+    fn read_page(&self, number: u32) -> Option<Vec<u8>> {
+        if number == 0 {
+            return None;
+        }
+        let mut page = vec![0u8; self.page_size];
+        let offset = (number as u64 - 1) * self.page_size as u64;
+        self.file.read_exact_at(&mut page, offset).ok()?;
+        Some(page)
+    }
+
+    /// Calls `visit` with the page and offset of every leaf cell of the
+    /// table b-tree rooted at `number`.
+    fn walk(&self, number: u32, depth: usize, visit: &mut dyn FnMut(&[u8], usize)) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let page = self.read_page(number)?;
+        let start = if number == 1 { FILE_HEADER_SIZE } else { 0 };
+        let header = page.get(start..start + 12)?;
+        let cells = u16::from_be_bytes([header[3], header[4]]) as usize;
+
+        match header[0] {
+            LEAF_TABLE_PAGE => {
+                for index in 0..cells {
+                    visit(&page, cell_offset(&page, start + 8, index)?);
+                }
+            }
+            INTERIOR_TABLE_PAGE => {
+                for index in 0..cells {
+                    // Each cell starts with the left child's page number
+                    let offset = cell_offset(&page, start + 12, index)?;
+                    self.walk(read_u32(&page, offset)?, depth + 1, visit)?;
+                }
+                self.walk(read_u32(&page, start + 8)?, depth + 1, visit)?;
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+/// Offset of cell `index` from the cell pointer array at `array`.
+fn cell_offset(page: &[u8], array: usize, index: usize) -> Option<usize> {
+    let pointer = page.get(array + index * 2..array + index * 2 + 2)?;
+    Some(u16::from_be_bytes([pointer[0], pointer[1]]) as usize)
+}
+
+fn read_u32(page: &[u8], offset: usize) -> Option<u32> {
+    let bytes = page.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Reads a big endian variable length integer, returning it with its size.
+fn read_varint(data: &[u8], offset: usize) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for index in 0..9 {
+        let byte = *data.get(offset + index)?;
+        if index == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+/// Root page from a schema row (type, name, tbl_name, rootpage, sql) when
+/// it describes `table`. The columns needed always fit in the part of the
+/// payload stored on the page itself; the sql text may overflow.
+fn schema_root(page: &[u8], cell: usize, table: &str) -> Option<u32> {
+    let (_, size) = read_varint(page, cell)?;
+    let (_, rowid_size) = read_varint(page, cell + size)?;
+    let payload = cell + size + rowid_size;
+
+    let (header_size, mut offset) = read_varint(page, payload)?;
+    let mut types = Vec::new();
+    while offset < header_size as usize && types.len() < 4 {
+        let (serial_type, size) = read_varint(page, payload + offset)?;
+        types.push(serial_type);
+        offset += size;
+    }
+    let [kind, name, _, rootpage] = types[..] else {
+        return None;
+    };
+
+    let mut column = payload + header_size as usize;
+    let kind_text = text(page, column, kind)?;
+    column += serial_size(kind)?;
+    let name_text = text(page, column, name)?;
+    column += serial_size(name)?;
+    column += serial_size(types[2])?;
+
+    if kind_text != b"table" || name_text != table.as_bytes() {
+        return None;
+    }
+    integer(page, column, rootpage).and_then(|root| u32::try_from(root).ok())
+}
+
+/// Size in bytes of a column with the given serial type.
+fn serial_size(serial_type: u64) -> Option<usize> {
+    Some(match serial_type {
+        0 | 8 | 9 => 0,
+        1..=4 => serial_type as usize,
+        5 => 6,
+        6 | 7 => 8,
+        // Blobs are even, text odd
+        12.. => ((serial_type - 12) / 2) as usize,
+        _ => return None,
+    })
+}
+
+fn text(page: &[u8], offset: usize, serial_type: u64) -> Option<&[u8]> {
+    if serial_type < 13 || serial_type % 2 == 0 {
+        return None;
+    }
+    page.get(offset..offset + serial_size(serial_type)?)
+}
+
+fn integer(page: &[u8], offset: usize, serial_type: u64) -> Option<u64> {
+    match serial_type {
+        8 => Some(0),
+        9 => Some(1),
+        1..=6 => {
+            let bytes = page.get(offset..offset + serial_size(serial_type)?)?;
+            Some(bytes.iter().fold(0, |value, &byte| (value << 8) | byte as u64))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const PAGE_SIZE: usize = 512;
+
+    /// A table b-tree page holding `cells`, with its header at `start`.
+    fn page(start: usize, right_child: Option<u32>, cells: &[Vec<u8>]) -> Vec<u8> {
+        let mut page = vec![0u8; PAGE_SIZE];
+        let header_size = if right_child.is_some() { 12 } else { 8 };
+        page[start] = if right_child.is_some() { INTERIOR_TABLE_PAGE } else { LEAF_TABLE_PAGE };
+        page[start + 3..start + 5].copy_from_slice(&(cells.len() as u16).to_be_bytes());
+        if let Some(child) = right_child {
+            page[start + 8..start + 12].copy_from_slice(&child.to_be_bytes());
+        }
+
+        let mut content = PAGE_SIZE;
+        for (index, cell) in cells.iter().enumerate() {
+            content -= cell.len();
+            page[content..content + cell.len()].copy_from_slice(cell);
+            let pointer = start + header_size + index * 2;
+            page[pointer..pointer + 2].copy_from_slice(&(content as u16).to_be_bytes());
+        }
+        page[start + 5..start + 7].copy_from_slice(&(content as u16).to_be_bytes());
+        page
+    }
+
+    /// Leaf cell of a row whose columns are all text but the last, an
+    /// integer below 128.
+    fn row(rowid: u8, texts: &[&str], integer: u8) -> Vec<u8> {
+        let mut header: Vec<u8> = texts.iter().map(|text| (13 + text.len() * 2) as u8).collect();
+        header.push(1);
+        header.insert(0, header.len() as u8 + 1);
+        let mut record = header;
+        for text in texts {
+            record.extend_from_slice(text.as_bytes());
+        }
+        record.push(integer);
+
+        let mut cell = vec![record.len() as u8, rowid];
+        cell.extend(record);
+        cell
+    }
+
+    /// Schema on page 1, then an interior page over two leaves of 2 and 3
+    /// rows.
+    fn database() -> Vec<u8> {
+        let mut first = page(FILE_HEADER_SIZE, None, &[
+            row(1, &["index", "Packages", "Packages"], 9),
+            row(2, &["table", "Packages", "Packages"], 2),
+        ]);
+        first[..HEADER.len()].copy_from_slice(HEADER);
+        first[16..18].copy_from_slice(&(PAGE_SIZE as u16).to_be_bytes());
+
+        let mut interior_cell = 3u32.to_be_bytes().to_vec();
+        interior_cell.push(2);
+        let leaf = |rows: std::ops::Range<u8>| {
+            page(0, None, &rows.map(|rowid| row(rowid, &[], rowid)).collect::<Vec<_>>())
+        };
+
+        [first, page(0, Some(4), &[interior_cell]), leaf(1..3), leaf(3..6)].concat()
+    }
+
+    fn open(contents: &[u8]) -> Option<Database> {
+        let fixture = Fixture::new();
+        fixture.file("/var/lib/rpm/rpmdb.sqlite", contents);
+        Database::open(fixture.source().open("/var/lib/rpm/rpmdb.sqlite").ok()?)
+    }
+
+    #[test]
+    fn counts_rows_across_pages() {
+        let database = open(&database()).unwrap();
+        assert_eq!(database.count_rows("Packages"), Some(5));
+        assert_eq!(database.count_rows("Missing"), None);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(open(b"not a database").is_none());
+        let mut odd_page_size = database();
+        odd_page_size[16..18].copy_from_slice(&1000u16.to_be_bytes());
+        assert!(open(&odd_page_size).is_none());
+    }
+
+    #[test]
+    fn reads_varints() {
+        assert_eq!(read_varint(&[0x05], 0), Some((5, 1)));
+        assert_eq!(read_varint(&[0x81, 0x00], 0), Some((128, 2)));
+        assert_eq!(read_varint(&[0xff; 9], 0), Some((u64::MAX, 9)));
+        assert_eq!(read_varint(&[0x81], 0), None);
+    }
+}